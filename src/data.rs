@@ -1,7 +1,19 @@
+mod assets;
+pub use assets
+:: {
+	resolve as resolve_asset,
+	resolve_all as resolve_all_assets,
+};
+
+mod mods;
+pub use mods
+:: {
+	Mod,
+};
+
 mod difficulty;
 pub use difficulty
 :: {
-	ALL_DIFFICULTY_SETTINGS,
 	Difficulty,
 	DifficultySettings,
 };
@@ -9,9 +21,150 @@ pub use difficulty
 mod character;
 pub use character
 :: {
-	ALL_CHARACTER_NAMES, ALL_CHARACTER_STATES, ALL_CHARACTER_TYPES,
-	CHARACTER_NAME_COUNT, CHARACTER_TYPE_COUNT,
-	CharacterStats
+	ALL_CHARACTER_STATES,
+	CharacterStats,
+	character_names,
+};
+
+mod character_type;
+pub use character_type
+:: {
+	CharacterType,
+};
+
+mod affix;
+pub use affix
+:: {
+	Affix,
+	AffixEffect,
+};
+
+mod interlude;
+pub use interlude
+:: {
+	InterludeEffect,
+	InterludeOption,
+};
+
+mod dialogue;
+pub use dialogue
+:: {
+	DialoguePool,
+	DialogueTrigger,
+};
+
+mod event;
+pub use event
+:: {
+	Event,
+	EventChoice,
+	EventOutcome,
+};
+
+mod shop;
+pub use shop
+:: {
+	ShopItem,
+};
+
+mod run_map;
+pub use run_map
+:: {
+	NodeKind,
+	RunMap,
+};
+
+mod user_folders;
+pub use user_folders
+:: {
+	USER_CONFIG_FOLDER,
+	USER_DATA_FOLDER,
+	migrate_legacy_runtime,
+	set_portable,
+};
+
+mod format;
+pub use format
+:: {
+	Format,
+	convert_file,
+	set_save_format,
+};
+
+mod integrity;
+pub use integrity
+:: {
+	chain_replay_hash,
+};
+
+mod storage;
+pub use storage
+:: {
+	use_memory_if_unwritable,
+	user_data,
+};
+
+mod migrations;
+
+mod save_slot;
+pub use save_slot
+:: {
+	SAVE_SLOT_COUNT,
+	Autosave,
+	SaveMetadata,
+	SaveSlot,
+};
+#[cfg(feature = "sqlite")]
+pub use save_slot::now;
+
+mod nemesis;
+pub use nemesis
+:: {
+	Nemesis,
+};
+
+mod profile;
+pub use profile
+:: {
+	DefeatedNemesis,
+	Profile,
+};
+
+mod names;
+pub use names
+:: {
+	EnemyTitle,
+};
+
+mod spawn;
+pub use spawn
+:: {
+	SpawnEntry,
+};
+
+mod validation;
+pub use validation
+:: {
+	ValidationIssue,
+};
+
+mod formula;
+pub use formula
+:: {
+	Formula,
+};
+
+mod balance;
+pub use balance
+:: {
+	BalanceSettings,
+};
+
+mod modifier;
+pub use modifier
+:: {
+	ModifierEffects,
+	RunModifier,
 };
 
 mod data_store;
@@ -27,4 +180,13 @@ pub use leaderboard
 	LeaderboardEntry,
 };
 
+#[cfg(feature = "sqlite")]
+mod run_history;
+#[cfg(feature = "sqlite")]
+pub use run_history
+:: {
+	RunFilter,
+	RunHistory,
+};
+
 pub use super::ASSETS_FOLDER;
\ No newline at end of file
@@ -0,0 +1,94 @@
+use lazy_static::lazy_static;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+	static ref AFFIXES: Vec<Affix> = {
+		println!("Loading elite affixes");
+		if !super::ASSETS_FOLDER.join("data/affixes.yaml").exists() {
+			init_affixes();
+		}
+		let path = super::assets::resolve("data/affixes.yaml");
+		match serde_yaml::from_reader(std::fs::File::open(&path).unwrap()) {
+			Ok(affixes) => affixes,
+			Err(error) => panic!("Failed to load {}: {}", path.display(), error),
+		}
+	};
+}
+
+/* Elites roll between one and this many affixes */
+const MAX_AFFIXES: usize = 2;
+
+/* A trait an elite enemy spawns with, e.g. Thorns or Enraged */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Affix {
+	pub id: String,
+	pub name: String,
+	pub description: String,
+	#[serde(default = "one")]
+	pub weight: f64,
+	/* Extra score for killing an enemy with this affix */
+	#[serde(default = "one_point")]
+	pub score_bonus: u32,
+	pub effect: AffixEffect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AffixEffect {
+	/* Sends a share of the damage taken back to the attacker */
+	Thorns { reflect: f64 },
+	/* Heals a share of max health at the start of each of its turns */
+	Regenerating { heal_per_turn: f64 },
+	/* Adds to the chance of evading every defense roll */
+	Evasive { evade_chance: f64 },
+	/* Hits harder once health drops below a share of max health */
+	Enraged { below_health: f64, attack_multiplier: f64 },
+}
+
+fn one() -> f64 {
+	1.0
+}
+
+fn one_point() -> u32 {
+	1
+}
+
+fn init_affixes() {
+	fn affix(id: &str, name: &str, description: &str, effect: AffixEffect) -> Affix {
+		Affix {
+			id: id.to_string(),
+			name: name.to_string(),
+			description: description.to_string(),
+			weight: 1.0,
+			score_bonus: 1,
+			effect,
+		}
+	}
+
+	let affixes = vec![
+		affix("thorns", "Thorns", "Reflects a third of the damage it takes.", AffixEffect::Thorns { reflect: 0.33 }),
+		affix("regenerating", "Regenerating", "Recovers a tenth of its health every turn.", AffixEffect::Regenerating { heal_per_turn: 0.1 }),
+		affix("evasive", "Evasive", "Much harder to hit.", AffixEffect::Evasive { evade_chance: 0.1 }),
+		affix("enraged", "Enraged", "Hits half again as hard below half health.", AffixEffect::Enraged { below_health: 0.5, attack_multiplier: 1.5 }),
+	];
+
+	super::storage::write_document(&super::storage::assets(), "data/affixes.yaml", &affixes).unwrap();
+}
+
+impl Affix {
+	/* Picks one or two different affixes by weight */
+	pub fn roll<R: Rng + ?Sized>(rng: &mut R) -> Vec<Affix> {
+		let count = rng.gen_range(1..=MAX_AFFIXES);
+		let mut remaining: Vec<&Affix> = AFFIXES.iter().filter(|affix| affix.weight > 0.0).collect();
+		let mut rolled = Vec::new();
+		while rolled.len() < count {
+			let distribution = match WeightedIndex::new(remaining.iter().map(|affix| affix.weight)) {
+				Ok(distribution) => distribution,
+				Err(_) => break,
+			};
+			rolled.push(remaining.remove(distribution.sample(rng)).clone());
+		}
+		rolled
+	}
+}
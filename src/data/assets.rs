@@ -0,0 +1,96 @@
+/*
+	Helpers for discovering data files in the assets folder.
+*/
+
+pub fn files(directory: &std::path::Path) -> Vec<std::path::PathBuf> {
+	let mut files: Vec<_> = match std::fs::read_dir(directory) {
+		Ok(entries) => entries
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.filter(|path| path.is_file())
+			.collect(),
+		Err(_) => Vec::new(),
+	};
+	files.sort();
+	files
+}
+
+pub fn files_with_extension(directory: &std::path::Path, extension: &str) -> Vec<std::path::PathBuf> {
+	files(directory)
+		.into_iter()
+		.filter(|path| path.extension().map_or(false, |e| e == extension))
+		.collect()
+}
+
+pub fn subfolders(directory: &std::path::Path) -> Vec<String> {
+	let mut folders: Vec<_> = match std::fs::read_dir(directory) {
+		Ok(entries) => entries
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.filter(|path| path.is_dir())
+			.map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+			.collect(),
+		Err(_) => Vec::new(),
+	};
+	folders.sort();
+	folders
+}
+
+/* "nightmare" -> "Nightmare" */
+pub fn title_case(id: &str) -> String {
+	let mut chars = id.chars();
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+/* The assets folder, every active mod in load order, then the player's own presets */
+fn layers() -> Vec<std::path::PathBuf> {
+	let mut layers: Vec<_> = std::iter::once(super::ASSETS_FOLDER.to_path_buf())
+		.chain(super::Mod::active().iter().map(|loaded| loaded.path.clone()))
+		.collect();
+	// A portable install keeps its presets in the assets folder itself
+	if *super::USER_CONFIG_FOLDER != *super::ASSETS_FOLDER {
+		layers.push(super::USER_CONFIG_FOLDER.to_path_buf());
+	}
+	layers
+}
+
+/* The last layer providing `relative` wins, falling back to the assets folder */
+pub fn resolve<P: AsRef<std::path::Path>>(relative: P) -> std::path::PathBuf {
+	layers()
+		.into_iter()
+		.rev()
+		.map(|layer| layer.join(relative.as_ref()))
+		.find(|path| path.exists())
+		.unwrap_or_else(|| super::ASSETS_FOLDER.join(relative.as_ref()))
+}
+
+/* Every layer that provides `relative`, base assets first */
+pub fn resolve_all<P: AsRef<std::path::Path>>(relative: P) -> Vec<std::path::PathBuf> {
+	layers()
+		.into_iter()
+		.map(|layer| layer.join(relative.as_ref()))
+		.filter(|path| path.exists())
+		.collect()
+}
+
+/* Files in `directory` across all layers; a mod's file replaces one with the same name */
+pub fn layered_files_with_extension(directory: &str, extension: &str) -> Vec<std::path::PathBuf> {
+	let mut files: std::collections::BTreeMap<std::ffi::OsString, std::path::PathBuf> = std::collections::BTreeMap::new();
+	for layer in layers() {
+		for path in files_with_extension(&layer.join(directory), extension) {
+			files.insert(path.file_name().unwrap().to_os_string(), path);
+		}
+	}
+	files.into_values().collect()
+}
+
+pub fn layered_subfolders(directory: &str) -> Vec<String> {
+	let mut folders: Vec<String> = layers()
+		.into_iter()
+		.flat_map(|layer| subfolders(&layer.join(directory)))
+		.collect();
+	folders.sort();
+	folders.dedup();
+	folders
+}
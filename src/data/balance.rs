@@ -0,0 +1,96 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use super::{CharacterStats, CharacterType, Formula, ValidationIssue};
+
+lazy_static! {
+	static ref BALANCE_SETTINGS: BalanceSettings = {
+		println!("Loading balance settings");
+		let path = super::ASSETS_FOLDER.join("data/balance.yaml");
+		if !path.exists() {
+			init_balance_settings();
+		}
+		match serde_yaml::from_reader(std::fs::File::open(&path).unwrap()) {
+			Ok(settings) => settings,
+			Err(error) => panic!("Failed to load {}: {}", path.display(), error),
+		}
+	};
+	static ref ISSUES: Vec<ValidationIssue> = BALANCE_SETTINGS.validate(&super::ASSETS_FOLDER.join("data/balance.yaml"));
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSettings {
+	pub max_health: Formula,
+	pub attack: StatRange,
+	pub defense: StatRange,
+	pub heal: StatRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatRange {
+	pub min: Formula,
+	pub max: Formula,
+}
+
+fn init_balance_settings() {
+	fn range(min: &str, max: &str) -> StatRange {
+		StatRange {
+			min: Formula::parse(min).unwrap(),
+			max: Formula::parse(max).unwrap(),
+		}
+	}
+
+	let balance_settings = BalanceSettings {
+		max_health: Formula::parse("vitality * 5").unwrap(),
+		attack: range("attack - 2", "attack + 2"),
+		defense: range("defense - 2", "defense + 2"),
+		heal: range("stamina - 5", "stamina + 5"),
+	};
+	super::storage::write_document(&super::storage::assets(), "data/balance.yaml", &balance_settings).unwrap();
+}
+
+impl BalanceSettings {
+	pub fn balance_settings() -> &'static BalanceSettings {
+		&BALANCE_SETTINGS
+	}
+
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&ISSUES
+	}
+
+	/* A character with no health would start every battle already dead */
+	fn validate(&self, file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+		for character_type in CharacterType::all() {
+			let health = self.max_health(&character_type.base_stats);
+			if health <= 0 {
+				issues.push(ValidationIssue::fatal(file, Some("max_health"), format!("`{}` gives {} health for the base stats of {}, it must be positive", self.max_health, health, character_type.id)));
+			}
+		}
+		issues
+	}
+
+	pub fn max_health(&self, stats: &CharacterStats) -> i32 {
+		self.max_health.evaluate(stats)
+	}
+
+	pub fn attack_range(&self, stats: &CharacterStats) -> (i32, i32) {
+		self.attack.evaluate(stats)
+	}
+
+	pub fn defense_range(&self, stats: &CharacterStats) -> (i32, i32) {
+		self.defense.evaluate(stats)
+	}
+
+	pub fn heal_range(&self, stats: &CharacterStats) -> (i32, i32) {
+		self.heal.evaluate(stats)
+	}
+}
+
+impl StatRange {
+	/* Returns (min, max), swapped if the formulas are written the wrong way around */
+	pub fn evaluate(&self, stats: &CharacterStats) -> (i32, i32) {
+		let min = self.min.evaluate(stats);
+		let max = self.max.evaluate(stats);
+		if min > max { (max, min) } else { (min, max) }
+	}
+}
@@ -0,0 +1,115 @@
+use lazy_static::lazy_static;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+use super::{CharacterStats, assets::{files_with_extension, layered_subfolders, resolve, resolve_all, title_case}};
+
+lazy_static! {
+	static ref CHARACTER_TYPES: Vec<CharacterType> = load_character_types();
+}
+
+/*
+	A playable/enemy character type. A type exists when it has both a texture
+	folder (`textures/characters/<id>`) and base stats
+	(`data/base_character_stats/<id>.yaml`), in the assets or in any mod;
+	`data/character_types.yaml` can optionally give it nicer names and a spawn weight.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterType {
+	pub id: String,
+	pub display_name: String,
+	pub enemy_display_name: String,
+	pub spawn_weight: f64,
+	pub base_stats: CharacterStats,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct CharacterTypeManifestEntry {
+	#[serde(default)]
+	display_name: Option<String>,
+	#[serde(default)]
+	enemy_display_name: Option<String>,
+	#[serde(default)]
+	spawn_weight: Option<f64>,
+}
+
+impl CharacterTypeManifestEntry {
+	fn merge(&mut self, other: CharacterTypeManifestEntry) {
+		self.display_name = other.display_name.or(self.display_name.take());
+		self.enemy_display_name = other.enemy_display_name.or(self.enemy_display_name.take());
+		self.spawn_weight = other.spawn_weight.or(self.spawn_weight);
+	}
+}
+
+type CharacterTypeManifest = std::collections::BTreeMap<String, CharacterTypeManifestEntry>;
+
+fn init_character_stats () {
+	fn create_and_save_as_yaml(name: &str, vitality: i32, attack: i32, defense: i32, stamina: i32) {
+		let stats = CharacterStats::new(vitality, attack, defense, stamina);
+		super::storage::write_document(&super::storage::assets(), &format!("data/base_character_stats/{}.yaml", name), &stats).unwrap();
+	}
+
+	create_and_save_as_yaml("adventurer", 1, 1, 2, 2);
+	create_and_save_as_yaml("female", 2, 1, 1, 2);
+	create_and_save_as_yaml("player", 1, 2, 1, 2);
+	create_and_save_as_yaml("soldier", 1, 2, 2, 1);
+	create_and_save_as_yaml("zombie", 1, 3, 1, 1);
+}
+
+fn load_character_types() -> Vec<CharacterType> {
+	if files_with_extension(&super::ASSETS_FOLDER.join("data/base_character_stats"), "yaml").is_empty() {
+		init_character_stats();
+	}
+
+	// Mods merge their manifest entries over the base one, type by type
+	let mut manifest = CharacterTypeManifest::new();
+	for manifest_path in resolve_all("data/character_types.yaml") {
+		let layer: CharacterTypeManifest = serde_yaml::from_reader(std::fs::File::open(&manifest_path).unwrap())
+			.unwrap_or_else(|error| panic!("Failed to load {}: {}", manifest_path.display(), error));
+		for (id, entry) in layer {
+			manifest.entry(id).or_default().merge(entry);
+		}
+	}
+
+	let character_types: Vec<CharacterType> = layered_subfolders("textures/characters")
+		.into_iter()
+		.filter_map(|id| {
+			let stats_path = resolve(format!("data/base_character_stats/{}.yaml", id));
+			if !stats_path.exists() {
+				eprintln!("Skipping character type {}: {} is missing", id, stats_path.display());
+				return None;
+			}
+
+			println!("Loading character stats for {}", id);
+			let base_stats = serde_yaml::from_reader(std::fs::File::open(&stats_path).unwrap())
+				.unwrap_or_else(|error| panic!("Failed to load {}: {}", stats_path.display(), error));
+			let entry = manifest.get(&id).cloned().unwrap_or_default();
+			let display_name = entry.display_name.unwrap_or_else(|| title_case(&id));
+			Some(CharacterType {
+				enemy_display_name: entry.enemy_display_name.unwrap_or_else(|| format!("Enemy {}", id)),
+				spawn_weight: entry.spawn_weight.unwrap_or(1.0).max(0.0),
+				display_name,
+				base_stats,
+				id,
+			})
+		})
+		.collect();
+
+	if character_types.is_empty() {
+		panic!("No character types found, every folder in textures/characters needs a matching data/base_character_stats/<name>.yaml");
+	}
+	character_types
+}
+
+impl CharacterType {
+	pub fn all() -> &'static [CharacterType] {
+		&CHARACTER_TYPES
+	}
+
+	/* Picks a type according to the manifest's spawn weights, uniformly if they're all 0 */
+	pub fn random_enemy<R: Rng + ?Sized>(rng: &mut R) -> &'static CharacterType {
+		match WeightedIndex::new(CHARACTER_TYPES.iter().map(|character_type| character_type.spawn_weight)) {
+			Ok(distribution) => &CHARACTER_TYPES[distribution.sample(rng)],
+			Err(_) => &CHARACTER_TYPES[rng.gen_range(0..CHARACTER_TYPES.len())],
+		}
+	}
+}
@@ -0,0 +1,132 @@
+use lazy_static::lazy_static;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use super::{CharacterType, ValidationIssue, assets::resolve};
+
+lazy_static! {
+	static ref DIALOGUE: LoadedDialogue = load_dialogue();
+}
+
+/* What just happened to an enemy, each one has its own pool of lines */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogueTrigger {
+	Spawn,
+	LowHealth,
+	Hit,
+	Miss,
+	Heal,
+	Cower,
+	KillPlayer,
+}
+
+/* The lines for one trigger and how likely the enemy is to say one of them */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DialoguePool {
+	#[serde(default = "one")]
+	pub chance: f64,
+	pub lines: Vec<String>,
+}
+
+type DialogueFile = BTreeMap<DialogueTrigger, DialoguePool>;
+
+struct LoadedDialogue {
+	default: DialogueFile,
+	/* Character type id -> pools that replace the default ones for that type */
+	by_type: HashMap<String, DialogueFile>,
+	issues: Vec<ValidationIssue>,
+}
+
+fn one() -> f64 {
+	1.0
+}
+
+fn load_dialogue() -> LoadedDialogue {
+	println!("Loading enemy dialogue");
+	if !super::ASSETS_FOLDER.join("data/dialogue/default.yaml").exists() {
+		init_dialogue();
+	}
+
+	let mut issues = Vec::new();
+	// A broken file is skipped, enemies then fall back to the built-in lines
+	let default = read_dialogue_file(&resolve("data/dialogue/default.yaml"), &mut issues).unwrap_or_else(default_dialogue);
+	let mut by_type = HashMap::new();
+	for character_type in CharacterType::all() {
+		let path = resolve(format!("data/dialogue/{}.yaml", character_type.id));
+		if path.exists() {
+			if let Some(dialogue) = read_dialogue_file(&path, &mut issues) {
+				by_type.insert(character_type.id.clone(), dialogue);
+			}
+		}
+	}
+	LoadedDialogue { default, by_type, issues }
+}
+
+fn read_dialogue_file(path: &std::path::Path, issues: &mut Vec<ValidationIssue>) -> Option<DialogueFile> {
+	let result = std::fs::File::open(path)
+		.map_err(|error| error.to_string())
+		.and_then(|file| serde_yaml::from_reader(file).map_err(|error| error.to_string()));
+	match result {
+		Ok(dialogue) => Some(dialogue),
+		Err(error) => {
+			issues.push(ValidationIssue::warning(path, None, format!("{}, the file is skipped", error)));
+			None
+		}
+	}
+}
+
+fn default_dialogue() -> DialogueFile {
+	fn pool(chance: f64, lines: &[&str]) -> DialoguePool {
+		DialoguePool { chance, lines: lines.iter().map(|line| line.to_string()).collect() }
+	}
+
+	vec![
+		(DialogueTrigger::Spawn, pool(1.0, &["You look lost.", "Another one?", "This ends here!"])),
+		(DialogueTrigger::LowHealth, pool(1.0, &["Not like this...", "I'm not done yet!", "You'll pay for that!"])),
+		(DialogueTrigger::Hit, pool(0.4, &["Take that!", "Too slow!", "Feel that?"])),
+		(DialogueTrigger::Miss, pool(0.4, &["Hold still!", "Lucky...", "Next time!"])),
+		(DialogueTrigger::Heal, pool(0.5, &["Just a scratch.", "Better.", "I can keep this up all day."])),
+		(DialogueTrigger::Cower, pool(0.5, &["Stay back!", "W-wait!", "I didn't sign up for this!"])),
+		(DialogueTrigger::KillPlayer, pool(1.0, &["Who's next?", "That was too easy.", "Stay down."])),
+	].into_iter().collect()
+}
+
+fn init_dialogue() {
+	if let Err(error) = super::storage::write_document(&super::storage::assets(), "data/dialogue/default.yaml", &default_dialogue()) {
+		eprintln!("{}", error);
+	}
+}
+
+impl DialogueTrigger {
+	/* Rare moments that always make it to the console, the rest are rate limited */
+	pub fn is_important(&self) -> bool {
+		matches!(self, DialogueTrigger::Spawn | DialogueTrigger::LowHealth | DialogueTrigger::KillPlayer)
+	}
+}
+
+impl DialoguePool {
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&DIALOGUE.issues
+	}
+
+	/*
+		Maybe picks a line a character of `character_type` says on `trigger`.
+		`last` is never picked again straight away unless it is the only line.
+	*/
+	pub fn line<R: Rng + ?Sized>(character_type: &str, trigger: DialogueTrigger, last: Option<&str>, rng: &mut R) -> Option<&'static str> {
+		let pool = DIALOGUE.by_type
+			.get(character_type)
+			.and_then(|dialogue| dialogue.get(&trigger))
+			.or_else(|| DIALOGUE.default.get(&trigger))?;
+		if pool.lines.is_empty() || !rng.gen_bool(pool.chance.clamp(0.0, 1.0)) {
+			return None;
+		}
+
+		let fresh: Vec<&String> = pool.lines.iter().filter(|line| Some(line.as_str()) != last).collect();
+		match fresh.is_empty() {
+			true => Some(&pool.lines[0]),
+			false => Some(fresh[rng.gen_range(0..fresh.len())]),
+		}
+	}
+}
@@ -1,22 +1,74 @@
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-
-pub static ALL_DIFFICULTY_SETTINGS: &[&str] = &["easy", "normal", "hard"];
+use super::{InterludeEffect, InterludeOption, SpawnEntry, ValidationIssue, assets::{files_with_extension, layered_files_with_extension, title_case}};
 
 lazy_static! {
-    static ref DIFFICULTY_SETTINGS: std::collections::HashMap<&'static str, DifficultySettings> = ALL_DIFFICULTY_SETTINGS.iter().map(|&difficulty| {
-		println!("Loading difficulty settings for {}", difficulty);
-		let path = super::ASSETS_FOLDER.join(format!("data/difficulty_settings/{}.yaml", difficulty));
-		if !path.exists() {
-			init_difficulty_settings();
+	static ref DIFFICULTY_SETTINGS: LoadedDifficultySettings = load_difficulty_settings();
+	/*
+		Presets saved from the editor since the game started, which replace any
+		loaded preset with the same id. They're leaked so they can be handed out
+		like the loaded ones; saving is rare enough for that not to matter.
+	*/
+	static ref SAVED_PRESETS: std::sync::Mutex<Vec<&'static DifficultySettings>> = std::sync::Mutex::new(Vec::new());
+}
+
+struct LoadedDifficultySettings {
+	settings: std::collections::HashMap<String, DifficultySettings>,
+	issues: Vec<ValidationIssue>,
+}
+
+fn load_difficulty_settings() -> LoadedDifficultySettings {
+	let directory = super::ASSETS_FOLDER.join("data/difficulty_settings");
+	if files_with_extension(&directory, "yaml").is_empty() {
+		init_difficulty_settings();
+	}
+
+	let mut settings = std::collections::HashMap::new();
+	let mut issues = Vec::new();
+	for path in layered_files_with_extension("data/difficulty_settings", "yaml") {
+		let id = path.file_stem().unwrap().to_string_lossy().to_lowercase();
+		println!("Loading difficulty settings for {}", id);
+
+		let parsed = std::fs::File::open(&path)
+			.map_err(|error| error.to_string())
+			.and_then(|file| serde_yaml::from_reader::<_, DifficultySettings>(file).map_err(|error| error.to_string()));
+		let mut difficulty_settings = match parsed {
+			Ok(difficulty_settings) => difficulty_settings,
+			Err(error) => {
+				issues.push(ValidationIssue::fatal(&path, None, error));
+				continue;
+			}
+		};
+
+		// The file name is the identifier, whatever the file itself claims
+		difficulty_settings.difficulty = Difficulty::from(id.clone());
+		if difficulty_settings.name.is_empty() {
+			difficulty_settings.name = title_case(&id);
 		}
-		let settings = serde_yaml::from_reader(std::fs::File::open(path).unwrap()).unwrap();
-		(difficulty, settings)
-	}).collect();
+
+		issues.extend(difficulty_settings.validate(&path));
+		settings.insert(id, difficulty_settings);
+	}
+
+	if settings.is_empty() && issues.is_empty() {
+		issues.push(ValidationIssue::fatal(&directory, None, String::from("no difficulty settings found")));
+	}
+
+	LoadedDifficultySettings { settings, issues }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DifficultySettings {
+	#[serde(default)]
+	pub name: String,
+	#[serde(default)]
+	pub rank: i32,
+	#[serde(default)]
+	pub description: String,
+	/* Presets made in the custom difficulty editor; never ranked against the official tiers */
+	#[serde(default)]
+	pub custom: bool,
+
 	pub enemy_base_attribute_points: i32,
 	pub enemy_attack_chance: f64,
 	pub enemy_heal_chance: f64,
@@ -26,69 +78,109 @@ pub struct DifficultySettings {
 	pub player_base_attribute_points: i32,
 	pub player_focus_chance: f64,
 	pub player_evade_chance: f64,
+	/* Chance for an enemy to spawn as an elite with random affixes */
+	#[serde(default)]
+	pub elite_chance: f64,
+	/* Which enemies show up and when; empty means every type by its manifest weight */
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub spawn_table: Vec<SpawnEntry>,
+	/* What the player can pick from between battles; an empty list skips the interlude */
+	#[serde(default = "InterludeOption::defaults")]
+	pub interlude_options: Vec<InterludeOption>,
+	#[serde(default)]
 	pub difficulty: Difficulty,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
-pub enum Difficulty {
-	Easy,
-	Normal,
-	Hard,
-}
+/*
+	Identifies a difficulty by the file stem of its settings file, e.g. "hard"
+	for `difficulty_settings/hard.yaml`. Older data stored the enum variant name
+	("Hard"), which is why identifiers are lowercased on the way in.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Difficulty(String);
 
 impl Difficulty {
-	pub const fn as_str(self) -> &'static str {
-		match self {
-			Difficulty::Easy => "easy",
-			Difficulty::Normal => "normal",
-			Difficulty::Hard => "hard",
-		}
+	pub fn as_str(&self) -> &str {
+		&self.0
 	}
 
-	pub const fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match (*self, *other) {
-			(Difficulty::Easy, Difficulty::Normal) => std::cmp::Ordering::Less,
-			(Difficulty::Easy, Difficulty::Hard) => std::cmp::Ordering::Less,
-			(Difficulty::Normal, Difficulty::Hard) => std::cmp::Ordering::Less,
-			(Difficulty::Normal, Difficulty::Easy) => std::cmp::Ordering::Greater,
-			(Difficulty::Hard, Difficulty::Easy) => std::cmp::Ordering::Greater,
-			(Difficulty::Hard, Difficulty::Normal) => std::cmp::Ordering::Greater,
-			_ => std::cmp::Ordering::Equal
+	pub fn settings(&self) -> Option<&'static DifficultySettings> {
+		let saved = SAVED_PRESETS.lock().unwrap().iter().find(|settings| settings.difficulty == *self).copied();
+		saved.or_else(|| DIFFICULTY_SETTINGS.settings.get(&self.0))
+	}
+
+	pub fn display_name(&self) -> String {
+		match self.settings() {
+			Some(settings) => settings.name.clone(),
+			None => title_case(&self.0),
 		}
-    }
+	}
+
+	/* Orders by the rank declared in the settings file. Unknown difficulties sort first. */
+	pub fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		let rank = |difficulty: &Difficulty| difficulty.settings().map(|settings| settings.rank).unwrap_or(i32::MIN);
+		rank(self).cmp(&rank(other)).then_with(|| self.0.cmp(&other.0))
+	}
+}
+
+impl From<String> for Difficulty {
+	fn from(id: String) -> Self {
+		Difficulty(id.to_lowercase())
+	}
+}
+
+impl From<Difficulty> for String {
+	fn from(difficulty: Difficulty) -> Self {
+		difficulty.0
+	}
 }
 
 impl std::fmt::Display for Difficulty {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let display_name = match self {
-			Difficulty::Easy => "Easy",
-			Difficulty::Normal => "Normal",
-			Difficulty::Hard => "Hard",
-		};
-        write!(f, "{} Difficulty", display_name)
+        write!(f, "{} Difficulty", self.display_name())
     }
 }
 
-
 fn init_difficulty_settings () {
-	use std::io::Write;
-
-	fn create_and_save_as_yaml(name: &str, enemy_base_attribute_points: i32, enemy_attack_chance: f64, enemy_heal_chance: f64, enemy_do_nothing_chance: f64, enemy_evade_chance: f64, player_base_attribute_points: i32, player_focus_chance: f64, player_evade_chance: f64, difficulty: Difficulty) {
-		let difficulty_settings = DifficultySettings::new(enemy_base_attribute_points, enemy_attack_chance, enemy_heal_chance, enemy_do_nothing_chance, enemy_evade_chance, player_base_attribute_points, player_focus_chance, player_evade_chance, difficulty);
-		let yaml = serde_yaml::to_string(&difficulty_settings).unwrap();
-		// create directories if they don't exist
-		std::fs::create_dir_all(super::ASSETS_FOLDER.join("data/difficulty_settings")).unwrap();
-		let mut file = std::fs::File::create(super::ASSETS_FOLDER.join(format!("data/difficulty_settings/{}.yaml", name))).unwrap();
-		file.write_all(yaml.as_bytes()).unwrap();
+	fn create_and_save_as_yaml(name: &str, rank: i32, description: &str, enemy_base_attribute_points: i32, enemy_attack_chance: f64, enemy_heal_chance: f64, enemy_do_nothing_chance: f64, enemy_evade_chance: f64, player_base_attribute_points: i32, player_focus_chance: f64, player_evade_chance: f64, elite_chance: f64, spawn_table: Vec<SpawnEntry>, interlude_options: Vec<InterludeOption>) {
+		let mut difficulty_settings = DifficultySettings::new(title_case(name), rank, description.to_string(), enemy_base_attribute_points, enemy_attack_chance, enemy_heal_chance, enemy_do_nothing_chance, enemy_evade_chance, player_base_attribute_points, player_focus_chance, player_evade_chance, Difficulty::from(name.to_string()));
+		difficulty_settings.elite_chance = elite_chance;
+		difficulty_settings.spawn_table = spawn_table;
+		difficulty_settings.interlude_options = interlude_options;
+		super::storage::write_document(&super::storage::assets(), &format!("data/difficulty_settings/{}.yaml", name), &difficulty_settings).unwrap();
 	}
 
-	create_and_save_as_yaml("easy", 3, 0.3, 0.3, 0.4, 0.05, 10, 0.7, 0.1, Difficulty::Easy);
-	create_and_save_as_yaml("normal", 5, 0.45, 0.3, 0.25, 0.07, 7, 0.5, 0.07, Difficulty::Normal);
-	create_and_save_as_yaml("hard", 8, 0.6, 0.32, 0.08, 0.1, 5, 0.2, 0.05, Difficulty::Hard);
+	create_and_save_as_yaml("easy", 0, "Weak enemies that often hesitate.", 3, 0.3, 0.3, 0.4, 0.05, 10, 0.7, 0.1, 0.05, vec![
+		SpawnEntry::new("adventurer", 1.0, 0, &["Lost Adventurer", "Clumsy Adventurer"]),
+		SpawnEntry::new("female", 1.0, 0, &[]),
+		SpawnEntry::new("player", 1.0, 0, &[]),
+		SpawnEntry::new("soldier", 0.5, 0, &["Deserter"]),
+		SpawnEntry::new("zombie", 0.5, 3, &["Shambling Zombie"]),
+	], vec![
+		InterludeOption::new("rest", "Rest", "Recover half of your health.", InterludeEffect::Rest { heal: 0.5 }),
+		InterludeOption::new("train", "Train", "Hit 50% harder for the next two battles.", InterludeEffect::Train { attack_multiplier: 1.5, battles: 2 }),
+		InterludeOption::new("scout", "Scout", "Find out who you will face next.", InterludeEffect::Scout),
+	]);
+	create_and_save_as_yaml("normal", 1, "A fair fight.", 5, 0.45, 0.3, 0.25, 0.07, 7, 0.5, 0.07, 0.1, Vec::new(), InterludeOption::defaults());
+	create_and_save_as_yaml("hard", 2, "Strong, aggressive enemies and fewer points to spend.", 8, 0.6, 0.32, 0.08, 0.1, 5, 0.2, 0.05, 0.2, vec![
+		SpawnEntry::new("adventurer", 0.5, 0, &[]),
+		SpawnEntry::new("female", 0.5, 0, &[]),
+		SpawnEntry::new("player", 0.5, 0, &[]),
+		SpawnEntry::new("soldier", 2.0, 0, &["Veteran Soldier", "Royal Guard", "Mercenary"]),
+		SpawnEntry::new("zombie", 1.0, 0, &["Ravenous Zombie"]),
+	], vec![
+		InterludeOption::new("rest", "Rest", "Recover a fifth of your health.", InterludeEffect::Rest { heal: 0.2 }),
+		InterludeOption::new("train", "Train", "Hit 25% harder in the next battle.", InterludeEffect::Train { attack_multiplier: 1.25, battles: 1 }),
+		InterludeOption::new("scout", "Scout", "Find out who you will face next.", InterludeEffect::Scout),
+	]);
 }
 
 impl DifficultySettings {
 	pub fn new(
+		name: String,
+		rank: i32,
+		description: String,
 		enemy_base_attribute_points: i32, 
 		enemy_attack_chance: f64, 
 		enemy_heal_chance: f64, 
@@ -101,6 +193,10 @@ impl DifficultySettings {
 	) -> DifficultySettings 
 	{
 		DifficultySettings {
+			name,
+			rank,
+			description,
+			custom: false,
 			enemy_base_attribute_points,
 			enemy_attack_chance,
 			enemy_heal_chance,
@@ -109,11 +205,258 @@ impl DifficultySettings {
 			player_base_attribute_points,
 			player_focus_chance,
 			player_evade_chance,
+			elite_chance: 0.0,
+			spawn_table: Vec::new(),
+			interlude_options: InterludeOption::defaults(),
 			difficulty,
 		}
 	}
 
-	pub fn difficulty_settings() -> &'static std::collections::HashMap<&'static str, DifficultySettings> {
-		&DIFFICULTY_SETTINGS
+	pub fn difficulty_settings() -> &'static std::collections::HashMap<String, DifficultySettings> {
+		&DIFFICULTY_SETTINGS.settings
+	}
+
+	/* Everything that was wrong with the settings files when they were loaded */
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&DIFFICULTY_SETTINGS.issues
+	}
+
+	/* The official tiers, easiest first */
+	pub fn official() -> Vec<&'static DifficultySettings> {
+		DifficultySettings::sorted().into_iter().filter(|settings| !settings.custom).collect()
+	}
+
+	/* Presets saved from the custom difficulty editor */
+	pub fn custom_presets() -> Vec<&'static DifficultySettings> {
+		DifficultySettings::sorted().into_iter().filter(|settings| settings.custom).collect()
+	}
+
+	/* All known difficulties, easiest first */
+	pub fn sorted() -> Vec<&'static DifficultySettings> {
+		let saved = SAVED_PRESETS.lock().unwrap().clone();
+		let mut settings: Vec<_> = DIFFICULTY_SETTINGS.settings.values()
+			.filter(|settings| !saved.iter().any(|preset| preset.difficulty == settings.difficulty))
+			.collect();
+		settings.extend(saved);
+		settings.sort_by(|a, b| a.difficulty.cmp(&b.difficulty));
+		settings
+	}
+
+	/*
+		Checks every field and reports problems against `file`. The enemy action
+		weights are normalized in place so they always sum to 1; anything that
+		can't be fixed up sensibly is reported as fatal.
+	*/
+	pub fn validate(&mut self, file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+
+		let points = [
+			("enemy_base_attribute_points", self.enemy_base_attribute_points),
+			("player_base_attribute_points", self.player_base_attribute_points),
+		];
+		for &(field, value) in points.iter() {
+			if value <= 0 {
+				issues.push(ValidationIssue::fatal(file, Some(field), format!("must be positive, got {}", value)));
+			}
+		}
+
+		let probabilities = [
+			("enemy_attack_chance", self.enemy_attack_chance),
+			("enemy_heal_chance", self.enemy_heal_chance),
+			("enemy_do_nothing_chance", self.enemy_do_nothing_chance),
+			("enemy_evade_chance", self.enemy_evade_chance),
+			("player_focus_chance", self.player_focus_chance),
+			("player_evade_chance", self.player_evade_chance),
+			("elite_chance", self.elite_chance),
+		];
+		let mut probabilities_valid = true;
+		for &(field, value) in probabilities.iter() {
+			if !(0.0..=1.0).contains(&value) {
+				probabilities_valid = false;
+				issues.push(ValidationIssue::fatal(file, Some(field), format!("must be between 0 and 1, got {}", value)));
+			}
+		}
+
+		if probabilities_valid {
+			let total = self.enemy_attack_chance + self.enemy_heal_chance + self.enemy_do_nothing_chance;
+			if total <= 0.0 {
+				issues.push(ValidationIssue::fatal(file, Some("enemy_attack_chance"), String::from("enemy_attack_chance, enemy_heal_chance and enemy_do_nothing_chance can't all be 0")));
+			} else if (total - 1.0).abs() > 1e-6 {
+				issues.push(ValidationIssue::warning(file, Some("enemy_attack_chance"), format!("enemy_attack_chance + enemy_heal_chance + enemy_do_nothing_chance sum to {}, normalizing to 1", total)));
+				self.enemy_attack_chance /= total;
+				self.enemy_heal_chance /= total;
+				self.enemy_do_nothing_chance /= total;
+			}
+		}
+
+		issues.extend(SpawnEntry::validate(&mut self.spawn_table, file));
+		issues.extend(InterludeOption::validate(&self.interlude_options, file));
+		issues
+	}
+
+	/*
+		Writes these settings as a custom preset named after `name`, e.g.
+		"My Run" ends up in `data/difficulty_settings/my_run.yaml` in the user
+		config folder rather than the assets, so updating the game doesn't
+		remove it. Official tiers can't be overwritten. The preset can be
+		picked right away. Returns where it was saved.
+	*/
+	pub fn save_as_preset(&mut self, name: &str) -> Result<String, String> {
+		let id = preset_id(name);
+		if id.is_empty() {
+			return Err(String::from("The preset needs a name"));
+		}
+		if DifficultySettings::official().iter().any(|settings| settings.difficulty.as_str() == id) {
+			return Err(format!("\"{}\" is an official difficulty and can't be overwritten", name));
+		}
+
+		let file = format!("data/difficulty_settings/{}.yaml", id);
+		let issues = self.validate(std::path::Path::new(&file));
+		if let Some(issue) = issues.iter().find(|issue| issue.is_fatal()) {
+			return Err(format!("{}: {}", issue.field.as_deref().unwrap_or("settings"), issue.message));
+		}
+
+		self.name = name.trim().to_string();
+		self.description = String::from("Custom preset");
+		self.custom = true;
+		self.difficulty = Difficulty::from(id);
+
+		let storage = super::storage::user_config();
+		super::storage::write_document(&*storage, &file, self)?;
+
+		let mut saved = SAVED_PRESETS.lock().unwrap();
+		saved.retain(|preset| preset.difficulty != self.difficulty);
+		saved.push(Box::leak(Box::new(self.clone())));
+		Ok(storage.location(&file))
+	}
+
+	/*
+		Rough per-turn expectations for an average enemy fighting an average
+		player, used to explain what a set of numbers means in practice.
+	*/
+	pub fn summary(&self) -> DifficultySummary {
+		let balance = super::BalanceSettings::balance_settings();
+		let base_stats: Vec<_> = super::CharacterType::all().iter().map(|character_type| character_type.base_stats).collect();
+		let average_stats = |points: i32| {
+			let count = base_stats.len().max(1) as f64;
+			let average = |stat: fn(&super::CharacterStats) -> i32| {
+				(base_stats.iter().map(stat).sum::<i32>() as f64 / count + points as f64 / 4.0).round() as i32
+			};
+			super::CharacterStats::new(average(|s| s.vitality), average(|s| s.attack), average(|s| s.defense), average(|s| s.stamina))
+		};
+		let average_roll = |(min, max): (i32, i32)| (min.max(1) + max.max(1)) as f64 / 2.0;
+		let hit_chance = |evade_chance: f64, defense_roll: f64| (1.0 - evade_chance).powf(defense_roll);
+
+		let enemy = average_stats(self.enemy_base_attribute_points);
+		let player = average_stats(self.player_base_attribute_points);
+
+		let total = self.enemy_attack_chance + self.enemy_heal_chance + self.enemy_do_nothing_chance;
+		let (attack_chance, heal_chance) = match total > 0.0 {
+			true => (self.enemy_attack_chance / total, self.enemy_heal_chance / total),
+			false => (0.0, 0.0),
+		};
+
+		let player_hit_chance = hit_chance(self.player_evade_chance, average_roll(balance.defense_range(&player)));
+		let enemy_hit_chance = hit_chance(self.enemy_evade_chance, average_roll(balance.defense_range(&enemy)));
+		let player_damage = average_roll(balance.attack_range(&player)) * enemy_hit_chance;
+		let enemy_health = balance.max_health(&enemy);
+
+		DifficultySummary {
+			enemy_health,
+			player_health: balance.max_health(&player),
+			enemy_damage_per_turn: attack_chance * average_roll(balance.attack_range(&enemy)) * player_hit_chance,
+			enemy_healing_per_turn: heal_chance * average_roll(balance.heal_range(&enemy)),
+			player_damage_per_attack: player_damage,
+			attacks_to_kill_enemy: match player_damage > 0.0 {
+				true => (enemy_health as f64 / player_damage).ceil(),
+				false => f64::INFINITY,
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultySummary {
+	pub enemy_health: i32,
+	pub player_health: i32,
+	pub enemy_damage_per_turn: f64,
+	pub enemy_healing_per_turn: f64,
+	pub player_damage_per_attack: f64,
+	pub attacks_to_kill_enemy: f64,
+}
+
+fn preset_id(name: &str) -> String {
+	let id: String = name.trim().to_lowercase().chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect();
+	id.trim_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn settings(attack: f64, heal: f64, do_nothing: f64) -> DifficultySettings {
+		DifficultySettings::new(String::from("Test"), 0, String::new(), 10, attack, heal, do_nothing, 0.1, 10, 0.2, 0.1, Difficulty::from(String::from("test")))
+	}
+
+	fn validate(settings: &mut DifficultySettings) -> Vec<ValidationIssue> {
+		settings.validate(std::path::Path::new("test.yaml"))
+	}
+
+	#[test]
+	fn valid_settings_have_no_issues() {
+		let mut valid = settings(0.6, 0.3, 0.1);
+		assert_eq!(validate(&mut valid), Vec::new());
+		assert_eq!(valid, settings(0.6, 0.3, 0.1));
+	}
+
+	#[test]
+	fn all_zero_action_weights_are_fatal() {
+		let issues = validate(&mut settings(0.0, 0.0, 0.0));
+		assert_eq!(issues.len(), 1);
+		assert!(issues[0].is_fatal());
+		assert_eq!(issues[0].field.as_deref(), Some("enemy_attack_chance"));
+	}
+
+	#[test]
+	fn out_of_range_values_are_fatal_and_left_alone() {
+		let mut negative = settings(0.5, -0.5, 0.2);
+		let issues = validate(&mut negative);
+		assert_eq!(issues.len(), 1);
+		assert!(issues[0].is_fatal());
+		assert_eq!(issues[0].field.as_deref(), Some("enemy_heal_chance"));
+		assert_eq!(negative, settings(0.5, -0.5, 0.2));
+
+		let mut too_likely = settings(0.5, 0.3, 0.2);
+		too_likely.elite_chance = 1.5;
+		too_likely.enemy_base_attribute_points = 0;
+		let issues = validate(&mut too_likely);
+		let fields: Vec<_> = issues.iter().map(|issue| issue.field.as_deref()).collect();
+		assert_eq!(fields, vec![Some("enemy_base_attribute_points"), Some("elite_chance")]);
+		assert!(issues.iter().all(ValidationIssue::is_fatal));
+	}
+
+	#[test]
+	fn weights_that_dont_sum_to_one_are_normalized_with_a_warning() {
+		let mut doubled = settings(1.0, 0.6, 0.4);
+		let issues = validate(&mut doubled);
+		assert_eq!(issues.len(), 1);
+		assert!(!issues[0].is_fatal());
+		assert!((doubled.enemy_attack_chance - 0.5).abs() < 1e-9);
+		assert!((doubled.enemy_heal_chance - 0.3).abs() < 1e-9);
+		assert!((doubled.enemy_do_nothing_chance - 0.2).abs() < 1e-9);
+
+		// Normalized settings are valid as they are
+		assert_eq!(validate(&mut doubled), Vec::new());
+	}
+
+	#[test]
+	fn broken_interlude_options_are_fatal() {
+		let mut settings = settings(0.6, 0.3, 0.1);
+		settings.interlude_options = vec![InterludeOption::new("rest", "Rest", "", InterludeEffect::Rest { heal: 2.0 })];
+		let issues = validate(&mut settings);
+		assert_eq!(issues.len(), 1);
+		assert!(issues[0].is_fatal());
 	}
 }
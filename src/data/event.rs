@@ -0,0 +1,171 @@
+use lazy_static::lazy_static;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+use super::{CharacterStats, ValidationIssue, assets::layered_files_with_extension};
+
+lazy_static! {
+	static ref EVENTS: LoadedEvents = load_events();
+}
+
+const MIN_CHOICES: usize = 2;
+const MAX_CHOICES: usize = 4;
+
+struct LoadedEvents {
+	events: Vec<Event>,
+	issues: Vec<ValidationIssue>,
+}
+
+/*
+	A short story the player walks into on an Event node of the map. Events
+	live in the `.yaml` files in `data/events` and are picked by weight among
+	those allowed at the current depth of the run.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+	pub id: String,
+	pub title: String,
+	pub description: String,
+	#[serde(default = "one")]
+	pub weight: f64,
+	#[serde(default)]
+	pub min_depth: u32,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub max_depth: Option<u32>,
+	pub choices: Vec<EventChoice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventChoice {
+	pub text: String,
+	/* What the player reads after picking this choice */
+	pub result: String,
+	#[serde(default)]
+	pub outcomes: Vec<EventOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventOutcome {
+	/* Heals a share of max health, or takes it away when negative. Never kills */
+	Health { amount: f64 },
+	/* Permanently adds to the player's stats; values may be negative */
+	Stats { stats: CharacterStats },
+	/* A named keepsake that adds its stats to the player's for the rest of the run */
+	Item { name: String, stats: CharacterStats },
+	/* The waiting enemy attacks before the player can move on */
+	Battle,
+}
+
+fn one() -> f64 {
+	1.0
+}
+
+fn load_events() -> LoadedEvents {
+	if !super::ASSETS_FOLDER.join("data/events").exists() {
+		init_events();
+	}
+
+	let mut events: Vec<Event> = Vec::new();
+	let mut issues = Vec::new();
+	for path in layered_files_with_extension("data/events", "yaml") {
+		println!("Loading events from {}", path.display());
+		let loaded: Vec<Event> = match serde_yaml::from_reader(std::fs::File::open(&path).unwrap()) {
+			Ok(loaded) => loaded,
+			Err(error) => {
+				issues.push(ValidationIssue::fatal(&path, None, error.to_string()));
+				continue;
+			}
+		};
+		for event in loaded {
+			issues.extend(event.validate(&path));
+			// A later file can override an event by reusing its id
+			events.retain(|existing| existing.id != event.id);
+			events.push(event);
+		}
+	}
+	LoadedEvents { events, issues }
+}
+
+fn init_events() {
+	fn choice(text: &str, result: &str, outcomes: Vec<EventOutcome>) -> EventChoice {
+		EventChoice { text: text.to_string(), result: result.to_string(), outcomes }
+	}
+
+	let events = vec![
+		Event {
+			id: String::from("shrine"),
+			title: String::from("A Forgotten Shrine"),
+			description: String::from("Moss covers a small stone shrine. A few coins still glint in its offering bowl."),
+			weight: 1.0,
+			min_depth: 0,
+			max_depth: None,
+			choices: vec![
+				choice("Pray", "A calm warmth spreads through you.", vec![EventOutcome::Health { amount: 0.25 }]),
+				choice("Take the coins", "The stones groan. Something has noticed you.", vec![EventOutcome::Stats { stats: CharacterStats::new(0, 1, 0, 0) }, EventOutcome::Health { amount: -0.15 }]),
+				choice("Walk on", "You leave the shrine as you found it.", vec![]),
+			],
+		},
+		Event {
+			id: String::from("ambush"),
+			title: String::from("Footsteps Behind You"),
+			description: String::from("Someone has been following you for a while now."),
+			weight: 0.6,
+			min_depth: 2,
+			max_depth: None,
+			choices: vec![
+				choice("Turn and fight", "You draw your weapon and face them.", vec![EventOutcome::Battle]),
+				choice("Run", "You get away, but not without a few scratches.", vec![EventOutcome::Health { amount: -0.1 }]),
+			],
+		},
+		Event {
+			id: String::from("old_soldier"),
+			title: String::from("An Old Soldier"),
+			description: String::from("A tired veteran offers you an old shield. It won't be needed where they are going."),
+			weight: 0.8,
+			min_depth: 0,
+			max_depth: Some(8),
+			choices: vec![
+				choice("Take the shield", "It is heavy, but it will serve you well.", vec![EventOutcome::Item { name: String::from("Battered Shield"), stats: CharacterStats::new(0, 0, 2, -1) }]),
+				choice("Share your food instead", "The veteran tells you stories of old battles until nightfall.", vec![EventOutcome::Stats { stats: CharacterStats::new(0, 0, 0, 1) }]),
+			],
+		},
+	];
+
+	super::storage::write_document(&super::storage::assets(), "data/events/roadside.yaml", &events).unwrap();
+}
+
+impl Event {
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&EVENTS.issues
+	}
+
+	/* Picks an event by weight among those allowed at `depth`, or None if there are none */
+	pub fn pick<R: Rng + ?Sized>(depth: u32, rng: &mut R) -> Option<&'static Event> {
+		let eligible: Vec<&Event> = EVENTS.events
+			.iter()
+			.filter(|event| event.weight > 0.0 && event.min_depth <= depth && event.max_depth.map_or(true, |max| depth <= max))
+			.collect();
+		let distribution = WeightedIndex::new(eligible.iter().map(|event| event.weight)).ok()?;
+		Some(eligible[distribution.sample(rng)])
+	}
+
+	fn validate(&self, file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+		if !(MIN_CHOICES..=MAX_CHOICES).contains(&self.choices.len()) {
+			issues.push(ValidationIssue::fatal(file, Some("choices"), format!("{} must have between {} and {} choices, got {}", self.id, MIN_CHOICES, MAX_CHOICES, self.choices.len())));
+		}
+		if self.max_depth.map_or(false, |max| max < self.min_depth) {
+			issues.push(ValidationIssue::fatal(file, Some("max_depth"), format!("{} has a max_depth below its min_depth", self.id)));
+		}
+		for choice in &self.choices {
+			for outcome in &choice.outcomes {
+				if let EventOutcome::Health { amount } = outcome {
+					if !(-1.0..=1.0).contains(amount) {
+						issues.push(ValidationIssue::fatal(file, Some("outcomes"), format!("{} must change health by between -1 and 1 of max health, got {}", self.id, amount)));
+					}
+				}
+			}
+		}
+		issues
+	}
+}
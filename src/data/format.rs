@@ -0,0 +1,107 @@
+/*
+	The file formats documents can be stored in, picked by file extension.
+
+	Every format holds the same document tree that YAML would, so a file can be
+	converted to any other format and back without losing anything, and old
+	saves are upgraded the same way whatever they were written in. That also
+	means RON files spell enums as `"Idle"` or `{"Hit": (...)}` rather than
+	RON's own enum syntax.
+*/
+
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_yaml::Value;
+use std::sync::RwLock;
+
+lazy_static! {
+	static ref SAVE_FORMAT: RwLock<Format> = RwLock::new(Format::Yaml);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Yaml,
+	Json,
+	Ron,
+	/* Compact binary, for saves nobody needs to read by hand */
+	MessagePack,
+}
+
+/* The format new saves are written in, existing saves are read in whatever format they have */
+pub fn save_format() -> Format {
+	*SAVE_FORMAT.read().unwrap()
+}
+
+pub fn set_save_format(format: Format) {
+	*SAVE_FORMAT.write().unwrap() = format;
+}
+
+impl Format {
+	pub const ALL: [Format; 4] = [Format::Yaml, Format::Json, Format::Ron, Format::MessagePack];
+
+	pub fn extension(&self) -> &'static str {
+		match self {
+			Format::Yaml => "yaml",
+			Format::Json => "json",
+			Format::Ron => "ron",
+			Format::MessagePack => "msgpack",
+		}
+	}
+
+	pub fn from_extension(extension: &str) -> Option<Format> {
+		match extension.to_lowercase().as_str() {
+			"yaml" | "yml" => Some(Format::Yaml),
+			"json" => Some(Format::Json),
+			"ron" => Some(Format::Ron),
+			"msgpack" | "mpk" => Some(Format::MessagePack),
+			_ => None,
+		}
+	}
+
+	/* The format of a document called `name`, e.g. "saves/slot_1.msgpack" */
+	pub fn of(name: &str) -> Result<Format, String> {
+		std::path::Path::new(name)
+			.extension()
+			.and_then(|extension| Format::from_extension(&extension.to_string_lossy()))
+			.ok_or_else(|| format!("{} is not in a known format, use .yaml, .json, .ron or .msgpack", name))
+	}
+
+	pub fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, String> {
+		let document = serde_yaml::to_value(value).map_err(|error| error.to_string())?;
+		self.encode_document(&document)
+	}
+
+	pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, String> {
+		serde_yaml::from_value(self.decode_document(bytes)?).map_err(|error| error.to_string())
+	}
+
+	pub fn encode_document(&self, document: &Value) -> Result<Vec<u8>, String> {
+		match self {
+			Format::Yaml => serde_yaml::to_vec(document).map_err(|error| error.to_string()),
+			Format::Json => serde_json::to_vec_pretty(document).map_err(|error| error.to_string()),
+			Format::Ron => ron::ser::to_string_pretty(document, ron::ser::PrettyConfig::default())
+				.map(String::into_bytes)
+				.map_err(|error| error.to_string()),
+			Format::MessagePack => rmp_serde::to_vec_named(document).map_err(|error| error.to_string()),
+		}
+	}
+
+	pub fn decode_document(&self, bytes: &[u8]) -> Result<Value, String> {
+		match self {
+			Format::Yaml => serde_yaml::from_slice(bytes).map_err(|error| error.to_string()),
+			Format::Json => serde_json::from_slice(bytes).map_err(|error| error.to_string()),
+			Format::Ron => ron::de::from_bytes(bytes).map_err(|error| error.to_string()),
+			Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|error| error.to_string()),
+		}
+	}
+}
+
+/* Rewrites `input` as `output`, each in the format its extension names */
+pub fn convert_file(input: &std::path::Path, output: &std::path::Path) -> Result<(), String> {
+	let input_format = Format::of(&input.to_string_lossy())?;
+	let output_format = Format::of(&output.to_string_lossy())?;
+
+	let bytes = std::fs::read(input).map_err(|error| format!("Failed to open {}: {}", input.display(), error))?;
+	let document = input_format.decode_document(&bytes).map_err(|error| format!("{} is not valid {:?}: {}", input.display(), input_format, error))?;
+	let bytes = output_format.encode_document(&document).map_err(|error| format!("{} can't be written as {:?}: {}", output.display(), output_format, error))?;
+	std::fs::write(output, bytes).map_err(|error| format!("Failed to write {}: {}", output.display(), error))
+}
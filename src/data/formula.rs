@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use super::CharacterStats;
+
+/*
+	A tiny arithmetic language used by the balance file to derive values from
+	a character's stats, e.g. `10 + vitality * 5` or `(attack + 1) / 2`.
+	Supports integer literals, the four stat names, + - * /, unary minus and
+	parentheses. Everything is evaluated with integer arithmetic.
+*/
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Formula {
+	source: String,
+	expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaError {
+	pub source: String,
+	pub position: usize,
+	pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stat {
+	Vitality,
+	Attack,
+	Defense,
+	Stamina,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+	Number(i32),
+	Stat(Stat),
+	Negate(Box<Expr>),
+	Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Formula {
+	pub fn parse(source: &str) -> Result<Formula, FormulaError> {
+		let mut parser = Parser { source, chars: source.char_indices().collect(), index: 0 };
+		let expr = parser.expression()?;
+		parser.skip_whitespace();
+		if let Some(&(position, c)) = parser.chars.get(parser.index) {
+			return Err(parser.error(position, format!("unexpected '{}'", c)));
+		}
+
+		Ok(Formula { source: source.to_string(), expr })
+	}
+
+	pub fn evaluate(&self, stats: &CharacterStats) -> i32 {
+		self.expr.evaluate(stats)
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.source
+	}
+}
+
+impl Expr {
+	fn evaluate(&self, stats: &CharacterStats) -> i32 {
+		match self {
+			Expr::Number(value) => *value,
+			Expr::Stat(Stat::Vitality) => stats.vitality,
+			Expr::Stat(Stat::Attack) => stats.attack,
+			Expr::Stat(Stat::Defense) => stats.defense,
+			Expr::Stat(Stat::Stamina) => stats.stamina,
+			Expr::Negate(expr) => expr.evaluate(stats).wrapping_neg(),
+			Expr::Binary(operator, lhs, rhs) => {
+				let lhs = lhs.evaluate(stats);
+				let rhs = rhs.evaluate(stats);
+				match operator {
+					Operator::Add => lhs.saturating_add(rhs),
+					Operator::Subtract => lhs.saturating_sub(rhs),
+					Operator::Multiply => lhs.saturating_mul(rhs),
+					// Division by zero evaluates to 0 instead of bringing the game down
+					Operator::Divide => lhs.checked_div(rhs).unwrap_or(0),
+				}
+			}
+		}
+	}
+}
+
+struct Parser<'a> {
+	source: &'a str,
+	chars: Vec<(usize, char)>,
+	index: usize,
+}
+
+impl<'a> Parser<'a> {
+	// expression := term (('+' | '-') term)*
+	fn expression(&mut self) -> Result<Expr, FormulaError> {
+		let mut lhs = self.term()?;
+		loop {
+			let operator = match self.peek() {
+				Some('+') => Operator::Add,
+				Some('-') => Operator::Subtract,
+				_ => return Ok(lhs),
+			};
+			self.index += 1;
+			let rhs = self.term()?;
+			lhs = Expr::Binary(operator, Box::new(lhs), Box::new(rhs));
+		}
+	}
+
+	// term := factor (('*' | '/') factor)*
+	fn term(&mut self) -> Result<Expr, FormulaError> {
+		let mut lhs = self.factor()?;
+		loop {
+			let operator = match self.peek() {
+				Some('*') => Operator::Multiply,
+				Some('/') => Operator::Divide,
+				_ => return Ok(lhs),
+			};
+			self.index += 1;
+			let rhs = self.factor()?;
+			lhs = Expr::Binary(operator, Box::new(lhs), Box::new(rhs));
+		}
+	}
+
+	// factor := '-' factor | '(' expression ')' | number | stat
+	fn factor(&mut self) -> Result<Expr, FormulaError> {
+		self.skip_whitespace();
+		let position = self.position();
+		match self.peek() {
+			Some('-') => {
+				self.index += 1;
+				Ok(Expr::Negate(Box::new(self.factor()?)))
+			}
+			Some('(') => {
+				self.index += 1;
+				let expr = self.expression()?;
+				match self.peek() {
+					Some(')') => {
+						self.index += 1;
+						Ok(expr)
+					}
+					_ => Err(self.error(self.position(), String::from("expected ')'"))),
+				}
+			}
+			Some(c) if c.is_ascii_digit() => {
+				let literal = self.take_while(|c| c.is_ascii_digit());
+				literal.parse::<i32>()
+					.map(Expr::Number)
+					.map_err(|_| self.error(position, format!("number '{}' is too large", literal)))
+			}
+			Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+				let identifier = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+				match identifier.as_str() {
+					"vitality" => Ok(Expr::Stat(Stat::Vitality)),
+					"attack" => Ok(Expr::Stat(Stat::Attack)),
+					"defense" => Ok(Expr::Stat(Stat::Defense)),
+					"stamina" => Ok(Expr::Stat(Stat::Stamina)),
+					_ => Err(self.error(position, format!("unknown stat '{}'", identifier))),
+				}
+			}
+			Some(c) => Err(self.error(position, format!("unexpected '{}'", c))),
+			None => Err(self.error(position, String::from("unexpected end of formula"))),
+		}
+	}
+
+	fn peek(&mut self) -> Option<char> {
+		self.skip_whitespace();
+		self.chars.get(self.index).map(|&(_, c)| c)
+	}
+
+	fn position(&self) -> usize {
+		self.chars.get(self.index).map(|&(position, _)| position).unwrap_or(self.source.len())
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some((_, c)) = self.chars.get(self.index) {
+			if !c.is_whitespace() {
+				break;
+			}
+			self.index += 1;
+		}
+	}
+
+	fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+		let mut taken = String::new();
+		while let Some(&(_, c)) = self.chars.get(self.index) {
+			if !predicate(c) {
+				break;
+			}
+			taken.push(c);
+			self.index += 1;
+		}
+		taken
+	}
+
+	fn error(&self, position: usize, message: String) -> FormulaError {
+		FormulaError { source: self.source.to_string(), position, message }
+	}
+}
+
+impl std::convert::TryFrom<String> for Formula {
+	type Error = FormulaError;
+
+	fn try_from(source: String) -> Result<Self, Self::Error> {
+		Formula::parse(&source)
+	}
+}
+
+impl From<Formula> for String {
+	fn from(formula: Formula) -> Self {
+		formula.source
+	}
+}
+
+impl std::fmt::Display for Formula {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.source)
+	}
+}
+
+impl std::fmt::Display for FormulaError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} at column {} in formula `{}`", self.message, self.position + 1, self.source)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn evaluate(source: &str) -> i32 {
+		Formula::parse(source).unwrap().evaluate(&CharacterStats::new(4, 3, 2, 1))
+	}
+
+	fn error(source: &str) -> FormulaError {
+		Formula::parse(source).unwrap_err()
+	}
+
+	#[test]
+	fn operators_follow_precedence() {
+		assert_eq!(evaluate("2 + 3 * 4"), 14);
+		assert_eq!(evaluate("2 * 3 + 4"), 10);
+		assert_eq!(evaluate("10 - 4 - 3"), 3);
+		assert_eq!(evaluate("24 / 4 / 2"), 3);
+		assert_eq!(evaluate("7 / 2"), 3);
+		assert_eq!(evaluate("(2 + 3) * 4"), 20);
+		assert_eq!(evaluate("((1))"), 1);
+	}
+
+	#[test]
+	fn unary_minus_binds_tighter_than_operators() {
+		assert_eq!(evaluate("-2 * 3"), -6);
+		assert_eq!(evaluate("3 - -2"), 5);
+		assert_eq!(evaluate("--4"), 4);
+		assert_eq!(evaluate("-(1 + 2)"), -3);
+	}
+
+	#[test]
+	fn stats_are_read_from_the_character() {
+		assert_eq!(evaluate("vitality * 5"), 20);
+		assert_eq!(evaluate("attack + defense - stamina"), 4);
+		assert_eq!(evaluate(" ( attack+1 ) / 2 "), 2);
+	}
+
+	#[test]
+	fn division_by_zero_and_overflow_dont_panic() {
+		assert_eq!(evaluate("10 / 0"), 0);
+		assert_eq!(evaluate("10 / (stamina - 1)"), 0);
+		assert_eq!(evaluate("2147483647 + 1"), i32::MAX);
+		assert_eq!(evaluate("2147483647 * 2147483647"), i32::MAX);
+	}
+
+	#[test]
+	fn errors_point_at_the_problem() {
+		let unknown = error("vitality * strength");
+		assert_eq!(unknown.position, 11);
+		assert_eq!(unknown.message, "unknown stat 'strength'");
+		assert_eq!(unknown.to_string(), "unknown stat 'strength' at column 12 in formula `vitality * strength`");
+
+		assert_eq!(error("(1 + 2").message, "expected ')'");
+		assert_eq!(error("(1 + 2").position, 6);
+		assert_eq!(error("1 +").message, "unexpected end of formula");
+		assert_eq!(error("1 2").position, 2);
+		assert_eq!(error("1 $ 2").message, "unexpected '$'");
+		assert_eq!(error("99999999999").message, "number '99999999999' is too large");
+		assert_eq!(error("").position, 0);
+	}
+
+	#[test]
+	fn formulas_are_stored_as_written() {
+		let formula: Formula = serde_yaml::from_str("\"attack  + 1\"").unwrap();
+		assert_eq!(formula.as_str(), "attack  + 1");
+		assert_eq!(serde_yaml::to_string(&formula).unwrap().trim_start_matches("---").trim(), "attack  + 1");
+		assert!(serde_yaml::from_str::<Formula>("\"attack +\"").is_err());
+	}
+}
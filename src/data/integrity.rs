@@ -0,0 +1,80 @@
+/*
+	Keyed hashes (HMAC-SHA256) that show whether a leaderboard entry or a save
+	is exactly what the game wrote. The key is made the first time it's needed
+	and kept in the user data folder, so this stops scores from being edited by
+	hand, not someone willing to read the key and the source.
+*/
+
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use super::storage;
+
+const KEY_FILE: &str = "integrity.key";
+
+lazy_static! {
+	static ref KEY: Vec<u8> = load_key();
+}
+
+fn load_key() -> Vec<u8> {
+	let storage = storage::user_data();
+	if let Ok(Some(key)) = storage.read(KEY_FILE) {
+		if let Some(key) = from_hex(String::from_utf8_lossy(&key).trim()) {
+			return key;
+		}
+		eprintln!("{} is corrupt, a new key was made and everything signed with the old one is unverified", storage.location(KEY_FILE));
+	}
+
+	let key: [u8; 32] = rand::random();
+	if let Err(error) = storage.write(KEY_FILE, to_hex(&key).as_bytes()) {
+		eprintln!("{}, scores from this session can't be verified later", error);
+	}
+	key.to_vec()
+}
+
+fn mac() -> Hmac<Sha256> {
+	Hmac::<Sha256>::new_from_slice(&KEY).unwrap()
+}
+
+/* The signature of `contents`, as hex */
+pub fn sign(contents: &[u8]) -> String {
+	let mut mac = mac();
+	mac.update(contents);
+	to_hex(&mac.finalize().into_bytes())
+}
+
+pub fn verify(contents: &[u8], signature: &str) -> bool {
+	let signature = match from_hex(signature) {
+		Some(signature) => signature,
+		None => return false,
+	};
+	let mut mac = mac();
+	mac.update(contents);
+	mac.verify_slice(&signature).is_ok()
+}
+
+/*
+	Chains one more decision onto a run's replay hash. Playing the same seed
+	with the same decisions always ends on the same hash.
+*/
+pub fn chain_replay_hash(previous: &str, decision: &str) -> String {
+	let mut hasher = Sha256::new();
+	hasher.update(previous.as_bytes());
+	hasher.update(b"\n");
+	hasher.update(decision.as_bytes());
+	to_hex(&hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+	if text.is_empty() || text.len() % 2 != 0 || !text.is_ascii() {
+		return None;
+	}
+	(0..text.len())
+		.step_by(2)
+		.map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok())
+		.collect()
+}
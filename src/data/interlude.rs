@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use super::ValidationIssue;
+
+/* Something the player can do between two battles */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterludeOption {
+	pub id: String,
+	pub name: String,
+	pub description: String,
+	pub effect: InterludeEffect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InterludeEffect {
+	/* Heals a share of max health */
+	Rest { heal: f64 },
+	/* Multiplies attack for the next few battles */
+	Train { attack_multiplier: f64, battles: u32 },
+	/* Reveals the next enemy before the fight */
+	Scout,
+}
+
+impl InterludeOption {
+	pub fn new(id: &str, name: &str, description: &str, effect: InterludeEffect) -> InterludeOption {
+		InterludeOption {
+			id: id.to_string(),
+			name: name.to_string(),
+			description: description.to_string(),
+			effect,
+		}
+	}
+
+	/* Used by difficulties that don't list their own options */
+	pub fn defaults() -> Vec<InterludeOption> {
+		vec![
+			InterludeOption::new("rest", "Rest", "Recover a third of your health.", InterludeEffect::Rest { heal: 0.35 }),
+			InterludeOption::new("train", "Train", "Hit 30% harder for the next two battles.", InterludeEffect::Train { attack_multiplier: 1.3, battles: 2 }),
+			InterludeOption::new("scout", "Scout", "Find out who you will face next.", InterludeEffect::Scout),
+		]
+	}
+
+	pub fn validate(options: &[InterludeOption], file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+		for option in options {
+			match option.effect {
+				InterludeEffect::Rest { heal } if !(0.0..=1.0).contains(&heal) => {
+					issues.push(ValidationIssue::fatal(file, Some("interlude_options"), format!("{} must heal between 0 and 1 of max health, got {}", option.id, heal)));
+				}
+				InterludeEffect::Train { attack_multiplier, .. } if !(attack_multiplier > 0.0) => {
+					issues.push(ValidationIssue::fatal(file, Some("interlude_options"), format!("{} must have a positive attack_multiplier, got {}", option.id, attack_multiplier)));
+				}
+				_ => {}
+			}
+		}
+		issues
+	}
+}
@@ -1,9 +1,12 @@
-use super::Difficulty;
-use serde::{Deserialize, Serialize};
+use super::{Difficulty, RunModifier, integrity, save_slot::now, storage};
+#[cfg(feature = "sqlite")]
+use super::RunHistory;
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Leaderboard {
     pub capacity: usize,
+    #[serde(deserialize_with = "checked_entries")]
     pub entries: Vec<LeaderboardEntry>,
     #[serde(skip)]
     pub is_empty: bool,
@@ -14,23 +17,94 @@ pub struct LeaderboardEntry {
     pub name: String,
     pub score: u32,
     pub difficulty: Difficulty,
+    #[serde(default)]
+    pub custom: bool,
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+    #[serde(default = "default_score_multiplier")]
+    pub score_multiplier: f64,
+    /* The mods active during the run, as "id@version"; empty for an unmodded game */
+    #[serde(default)]
+    pub mods: Vec<String>,
+    #[serde(default)]
+    pub character_type: String,
+    #[serde(default)]
+    pub kills: u32,
+    /* Seconds since the unix epoch, 0 for runs from before it was recorded */
+    #[serde(default)]
+    pub finished_at: u64,
+    /* The seed the run's map was made from */
+    #[serde(default)]
+    pub seed: u64,
+    /* Every decision made during the run, see `integrity::chain_replay_hash` */
+    #[serde(default)]
+    pub replay_hash: String,
+    /* Empty for entries from before signing, and for runs loaded from a save that was edited */
+    #[serde(default)]
+    pub signature: String,
+    /* Whether the signature matched when the entry was loaded or signed, see `checked` */
+    #[serde(skip)]
+    pub(super) verified: bool,
+}
+
+fn default_score_multiplier() -> f64 {
+    1.0
+}
+
+/* Entries are checked once as they are read, sorting compares them far too often to check there */
+fn checked_entries<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<LeaderboardEntry>, D::Error> {
+    Ok(Vec::<LeaderboardEntry>::deserialize(deserializer)?.into_iter().map(LeaderboardEntry::checked).collect())
 }
 
 impl Leaderboard {
+    /* With the `sqlite` feature the leaderboard is read from the run history instead of its own file */
+    #[cfg(not(feature = "sqlite"))]
     pub fn make(capacity: Option<usize>) -> Self {
         Leaderboard::read_from_file(capacity)
     }
 
-    pub fn get(&self, index: usize) -> Option<&LeaderboardEntry> {
-        self.entries.get(index)
+    #[cfg(feature = "sqlite")]
+    pub fn make(capacity: Option<usize>) -> Self {
+        let capacity = capacity.unwrap_or(10);
+        Leaderboard::from_runs(capacity, RunHistory::leaderboard(capacity))
+    }
+
+    /* Ranks runs from the history and keeps the best `capacity` of every table */
+    #[cfg(feature = "sqlite")]
+    pub(super) fn from_runs(capacity: usize, runs: Vec<LeaderboardEntry>) -> Self {
+        let mut leaderboard = Leaderboard::new(capacity);
+        leaderboard.entries = runs;
+        leaderboard.sort();
+        leaderboard.remove_extras();
+        leaderboard.is_empty = leaderboard.entries.is_empty();
+        leaderboard
+    }
+
+    /* Forgets every score, used by Erase Data */
+    pub fn erase() -> Result<(), String> {
+        #[cfg(feature = "sqlite")]
+        RunHistory::clear();
+        storage::user_data().delete(Leaderboard::FILE)
+    }
+
+    /*
+        Modded runs are ranked separately, each mod set gets its own table, and
+        runs on custom difficulties get their own table next to the official one
+    */
+    pub fn entries_for(&self, mods: &[String], custom: bool) -> Vec<&LeaderboardEntry> {
+        self.entries.iter().filter(|entry| entry.mods == mods && entry.custom == custom).collect()
     }
 
     pub fn add_entry(&mut self, entry: LeaderboardEntry) {
+        let entry = entry.checked();
+        #[cfg(feature = "sqlite")]
+        RunHistory::record(&entry);
         self.entries.push(entry);
         
         self.sort();
         self.remove_extras();
 
+        #[cfg(not(feature = "sqlite"))]
         self.write_to_file();
     }
 
@@ -38,10 +112,22 @@ impl Leaderboard {
         self.entries.sort_by(|a, b| b.cmp(a));
     }
 
+    /* Entries are already sorted, so keep the first `capacity` of every table */
     fn remove_extras(&mut self) {
-        while self.entries.len() > self.capacity {
-            self.entries.pop();
-        }
+        let mut kept: Vec<(Vec<String>, bool, usize)> = Vec::new();
+        let capacity = self.capacity;
+        self.entries.retain(|entry| {
+            match kept.iter_mut().find(|(mods, custom, _)| *mods == entry.mods && *custom == entry.custom) {
+                Some((_, _, count)) => {
+                    *count += 1;
+                    *count <= capacity
+                }
+                None => {
+                    kept.push((entry.mods.clone(), entry.custom, 1));
+                    true
+                }
+            }
+        });
     }
 
     fn new(capacity: usize) -> Self {
@@ -52,51 +138,131 @@ impl Leaderboard {
         }
     }
 
-    #[allow(unused_must_use)]
+    pub const FILE: &'static str = "leaderboard.yaml";
+
+    #[cfg(not(feature = "sqlite"))]
     fn read_from_file(capacity: Option<usize>) -> Self {
         let capacity = capacity.unwrap_or(10);
-        let path = super::ASSETS_FOLDER.join(format!("data/runtime/leaderboard.yaml"));
-        
-        let parent = path.parent().unwrap();
-        if !parent.exists() {
-            std::fs::create_dir_all(parent);
-        }
 
-        let file = std::fs::File::open(path);
-        match file {
-            Err(_) => { 
+        match storage::read_document::<Leaderboard>(&*storage::user_data(), Leaderboard::FILE) {
+            Ok(None) => {
                 let leaderboard = Leaderboard::new(capacity);
                 leaderboard.write_to_file();
                 leaderboard
             }
-            Ok(file) => { 
-                let mut leaderboard: Leaderboard = serde_yaml::from_reader(file).unwrap();
+            Ok(Some(mut leaderboard)) => {
                 leaderboard.is_empty = leaderboard.entries.len() == 0;
                 leaderboard
             }
+            // Start over, the broken file stays until the next score replaces it
+            Err(error) => {
+                eprintln!("{}", error);
+                Leaderboard::new(capacity)
+            }
         }
     }
-    
-    #[allow(unused_must_use)]
+
+    #[cfg(not(feature = "sqlite"))]
     fn write_to_file(&self) {
-        let path = super::ASSETS_FOLDER.join(format!("data/runtime/leaderboard.yaml"));
-        
-        let parent = path.parent().unwrap();
-        if !parent.exists() {
-            std::fs::create_dir_all(parent);
+        if let Err(error) = storage::write_document(&*storage::user_data(), Leaderboard::FILE, self) {
+            eprintln!("{}", error);
         }
-
-        let file = std::fs::File::create(path).unwrap();
-        serde_yaml::to_writer(file, self);
     }
 }
 
 impl LeaderboardEntry {
-    pub fn new(name: String, score: u32, difficulty: Difficulty) -> LeaderboardEntry {
-        LeaderboardEntry { name, score, difficulty }
+    pub fn new(name: String, score: u32, difficulty: Difficulty, custom: bool) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name,
+            score,
+            difficulty,
+            custom,
+            modifiers: Vec::new(),
+            score_multiplier: 1.0,
+            mods: Vec::new(),
+            character_type: String::new(),
+            kills: 0,
+            finished_at: now(),
+            seed: 0,
+            replay_hash: String::new(),
+            signature: String::new(),
+            verified: false,
+        }
+    }
+
+    pub fn with_replay(mut self, seed: u64, replay_hash: String) -> Self {
+        self.seed = seed;
+        self.replay_hash = replay_hash;
+        self
+    }
+
+    /* Signs the entry as it is now, so it has to be the last thing done to it */
+    pub fn signed(mut self) -> Self {
+        self.signature = integrity::sign(&self.signed_contents());
+        self.verified = true;
+        self
+    }
+
+    /* Checks the signature against the entry as it is now and remembers the result for `is_verified` */
+    pub fn checked(mut self) -> Self {
+        self.verified = integrity::verify(&self.signed_contents(), &self.signature);
+        self
+    }
+
+    /* False if the entry was changed after the game wrote it, or was never signed */
+    pub fn is_verified(&self) -> bool {
+        self.verified
+    }
+
+    // Listed by hand rather than serializing the entry, so adding a field doesn't break every signature
+    fn signed_contents(&self) -> Vec<u8> {
+        serde_json::to_vec(&(
+            &self.name,
+            self.score,
+            self.difficulty.as_str(),
+            self.custom,
+            &self.modifiers,
+            self.score_multiplier,
+            &self.mods,
+            &self.character_type,
+            self.kills,
+            self.finished_at,
+            self.seed,
+            &self.replay_hash,
+        ))
+        .unwrap()
+    }
+
+    pub fn with_run(mut self, character_type: String, kills: u32) -> Self {
+        self.character_type = character_type;
+        self.kills = kills;
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifiers: &[RunModifier]) -> Self {
+        self.modifiers = modifiers.iter().map(|modifier| modifier.name.clone()).collect();
+        self.score_multiplier = RunModifier::combined_score_multiplier(modifiers);
+        self
+    }
+
+    pub fn with_mods(mut self, mods: Vec<String>) -> Self {
+        self.mods = mods;
+        self
     }
 
     pub fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Entries that can't be verified rank below everything else, whatever their score
+        let verified_cmp = self.verified.cmp(&other.verified);
+        if verified_cmp != std::cmp::Ordering::Equal {
+            return verified_cmp;
+        }
+
+        // Runs on custom difficulties always rank below the official tiers
+        let official_cmp = (!self.custom).cmp(&!other.custom);
+        if official_cmp != std::cmp::Ordering::Equal {
+            return official_cmp;
+        }
+
         let difficulty_cmp = self.difficulty.cmp(&other.difficulty);
         if difficulty_cmp != std::cmp::Ordering::Equal {
             return difficulty_cmp;
@@ -0,0 +1,122 @@
+use serde_yaml::{Mapping, Value};
+use sha2::{Digest, Sha256};
+
+/* Bump this and add a step to MIGRATIONS whenever the saved shape of GameData changes */
+pub const CURRENT_SAVE_VERSION: u64 = 2;
+
+/* Step `i` upgrades a save from version `i` to version `i + 1` */
+const MIGRATIONS: &[fn(Value) -> Result<Value, String>] = &[
+	from_unversioned,
+	add_run_tracking,
+];
+
+/*
+	Brings a save document up to CURRENT_SAVE_VERSION one step at a time.
+	Saves from a newer game, or ones a step can't make sense of, come back
+	as an error that can be shown to the player.
+*/
+pub fn migrate(mut document: Value) -> Result<Value, String> {
+	let mut version = match &document["version"] {
+		Value::Null => 0,
+		Value::Number(number) => number.as_u64().ok_or_else(|| format!("{} is not a valid save version", number))?,
+		other => return Err(format!("{:?} is not a valid save version", other)),
+	};
+	if version > CURRENT_SAVE_VERSION {
+		return Err(format!("This save was made by a newer version of the game (save version {}, this game reads up to {})", version, CURRENT_SAVE_VERSION));
+	}
+
+	while version < CURRENT_SAVE_VERSION {
+		document = MIGRATIONS[version as usize](document)
+			.map_err(|error| format!("Could not upgrade the save from version {}: {}", version, error))?;
+		version += 1;
+		match &mut document {
+			Value::Mapping(mapping) => { mapping.insert(Value::from("version"), Value::from(version)); }
+			_ => return Err(String::from("The save is not a YAML mapping")),
+		}
+	}
+	Ok(document)
+}
+
+/*
+	Version 0 covers both the single `current_game.yaml` from before save slots,
+	which is a bare GameData, and the first slot files that had metadata but no
+	version. Bare saves get wrapped and their metadata is read from the run.
+*/
+fn from_unversioned(document: Value) -> Result<Value, String> {
+	if !document["game"].is_null() {
+		return Ok(document);
+	}
+
+	fn field<'a>(document: &'a Value, path: &[&str]) -> Result<&'a Value, String> {
+		let value = path.iter().fold(document, |value, key| &value[*key]);
+		match value {
+			Value::Null => Err(format!("the save has no {}", path.join("."))),
+			value => Ok(value),
+		}
+	}
+
+	let mut metadata = Mapping::new();
+	metadata.insert(Value::from("name"), Value::from("Imported save"));
+	metadata.insert(Value::from("character_name"), field(&document, &["player", "name"])?.clone());
+	metadata.insert(Value::from("character_type"), field(&document, &["player", "character_type"])?.clone());
+	metadata.insert(Value::from("difficulty"), field(&document, &["difficulty_settings", "difficulty"])?.clone());
+	metadata.insert(Value::from("custom"), document["difficulty_settings"]["custom"].as_bool().map_or(Value::from(false), Value::from));
+	metadata.insert(Value::from("kills"), field(&document, &["enemies_killed"])?.clone());
+	metadata.insert(Value::from("playtime_secs"), Value::from(0));
+	metadata.insert(Value::from("saved_at"), Value::from(0));
+
+	let mut envelope = Mapping::new();
+	envelope.insert(Value::from("metadata"), Value::Mapping(metadata));
+	envelope.insert(Value::from("game"), document);
+	Ok(Value::Mapping(envelope))
+}
+
+/*
+	Version 2 tracks each run: an id shared by all of its saves, the time
+	played, the replay hash, whether it was verified, ironman and score spent
+	in shops. Fields missing from older saves get what a run started without
+	them would have had. The time played comes from the save's metadata.
+*/
+fn add_run_tracking(mut document: Value) -> Result<Value, String> {
+	let playtime_secs = document["metadata"]["playtime_secs"].as_u64().unwrap_or(0);
+	let run_id = document["metadata"]["run_id"].clone();
+	let game = match document.get_mut("game") {
+		Some(Value::Mapping(game)) => game,
+		_ => return Err(String::from("the save has no run")),
+	};
+	let defaults = [
+		("playtime_secs", Value::from(playtime_secs)),
+		("replay_hash", Value::from("")),
+		("unverified", Value::from(false)),
+		("ironman", Value::from(false)),
+		("score_spent", Value::from(0)),
+	];
+	for (key, value) in defaults.iter() {
+		if !game.contains_key(&Value::from(*key)) {
+			game.insert(Value::from(*key), value.clone());
+		}
+	}
+	// Saves of the same run share an id, so the metadata and the run must agree on it
+	let run_id = match (run_id.as_u64(), game.get(&Value::from("run_id")).and_then(Value::as_u64)) {
+		(Some(id), _) if id != 0 => id,
+		(_, Some(id)) if id != 0 => id,
+		_ => run_id_for(game)?,
+	};
+	game.insert(Value::from("run_id"), Value::from(run_id));
+	if let Some(Value::Mapping(metadata)) = document.get_mut("metadata") {
+		metadata.insert(Value::from("run_id"), Value::from(run_id));
+	}
+	Ok(document)
+}
+
+/*
+	An id for a save from before run ids. Saves are upgraded every time they
+	are read, so it has to come out the same each time rather than be random.
+*/
+fn run_id_for(game: &Mapping) -> Result<u64, String> {
+	let contents = serde_yaml::to_string(game).map_err(|error| error.to_string())?;
+	let hash = Sha256::digest(contents.as_bytes());
+	let mut bytes = [0; 8];
+	bytes.copy_from_slice(&hash[..8]);
+	Ok(u64::from_le_bytes(bytes).max(1))
+}
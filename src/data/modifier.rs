@@ -0,0 +1,120 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+	static ref RUN_MODIFIERS: Vec<RunModifier> = {
+		println!("Loading run modifiers");
+		let path = super::ASSETS_FOLDER.join("data/run_modifiers.yaml");
+		if !path.exists() {
+			init_run_modifiers();
+		}
+		match serde_yaml::from_reader(std::fs::File::open(&path).unwrap()) {
+			Ok(modifiers) => modifiers,
+			Err(error) => panic!("Failed to load {}: {}", path.display(), error),
+		}
+	};
+}
+
+/* An optional rule change picked before character creation */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunModifier {
+	pub id: String,
+	pub name: String,
+	pub description: String,
+	#[serde(default = "one")]
+	pub score_multiplier: f64,
+	#[serde(default)]
+	pub effects: ModifierEffects,
+}
+
+/* Every field is neutral by default so modifiers only list what they change */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModifierEffects {
+	pub player_attack_multiplier: f64,
+	pub player_health_multiplier: f64,
+	pub player_heal_multiplier: f64,
+	/* Share of damage dealt that the player heals back */
+	pub player_lifesteal: f64,
+	pub enemy_points_multiplier: f64,
+	pub enemy_health_multiplier: f64,
+	/* Scales how long enemies think before acting */
+	pub enemy_think_time_multiplier: f64,
+}
+
+fn one() -> f64 {
+	1.0
+}
+
+fn init_run_modifiers() {
+	fn modifier(id: &str, name: &str, description: &str, score_multiplier: f64, effects: ModifierEffects) -> RunModifier {
+		RunModifier {
+			id: id.to_string(),
+			name: name.to_string(),
+			description: description.to_string(),
+			score_multiplier,
+			effects,
+		}
+	}
+
+	let neutral = ModifierEffects::default();
+	let modifiers = vec![
+		modifier("glass_cannon", "Glass Cannon", "Deal double damage, but only have half the health.", 1.5,
+			ModifierEffects { player_attack_multiplier: 2.0, player_health_multiplier: 0.5, ..neutral }),
+		modifier("no_healing", "No Healing", "Healing is disabled for you.", 1.75,
+			ModifierEffects { player_heal_multiplier: 0.0, ..neutral }),
+		modifier("vampiric", "Vampiric", "Heal for a third of the damage you deal.", 0.8,
+			ModifierEffects { player_lifesteal: 0.33, ..neutral }),
+		modifier("swarm", "Swarm", "Enemies are weaker, but come at you much faster.", 1.25,
+			ModifierEffects { enemy_points_multiplier: 0.5, enemy_health_multiplier: 0.6, enemy_think_time_multiplier: 0.4, ..neutral }),
+	];
+
+	super::storage::write_document(&super::storage::assets(), "data/run_modifiers.yaml", &modifiers).unwrap();
+}
+
+impl RunModifier {
+	pub fn run_modifiers() -> &'static [RunModifier] {
+		&RUN_MODIFIERS
+	}
+
+	pub fn combined_effects(modifiers: &[RunModifier]) -> ModifierEffects {
+		modifiers.iter().fold(ModifierEffects::default(), |effects, modifier| effects.combine(&modifier.effects))
+	}
+
+	pub fn combined_score_multiplier(modifiers: &[RunModifier]) -> f64 {
+		modifiers.iter().map(|modifier| modifier.score_multiplier).product()
+	}
+
+	pub fn apply_score_multiplier(score: u32, modifiers: &[RunModifier]) -> u32 {
+		(score as f64 * RunModifier::combined_score_multiplier(modifiers)).round() as u32
+	}
+}
+
+impl ModifierEffects {
+	/* Multipliers stack multiplicatively, lifesteal stacks additively */
+	pub fn combine(self, other: &ModifierEffects) -> ModifierEffects {
+		ModifierEffects {
+			player_attack_multiplier: self.player_attack_multiplier * other.player_attack_multiplier,
+			player_health_multiplier: self.player_health_multiplier * other.player_health_multiplier,
+			player_heal_multiplier: self.player_heal_multiplier * other.player_heal_multiplier,
+			player_lifesteal: self.player_lifesteal + other.player_lifesteal,
+			enemy_points_multiplier: self.enemy_points_multiplier * other.enemy_points_multiplier,
+			enemy_health_multiplier: self.enemy_health_multiplier * other.enemy_health_multiplier,
+			enemy_think_time_multiplier: self.enemy_think_time_multiplier * other.enemy_think_time_multiplier,
+		}
+	}
+}
+
+impl Default for ModifierEffects {
+	fn default() -> Self {
+		ModifierEffects {
+			player_attack_multiplier: 1.0,
+			player_health_multiplier: 1.0,
+			player_heal_multiplier: 1.0,
+			player_lifesteal: 0.0,
+			enemy_points_multiplier: 1.0,
+			enemy_health_multiplier: 1.0,
+			enemy_think_time_multiplier: 1.0,
+		}
+	}
+}
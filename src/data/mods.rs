@@ -0,0 +1,152 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use super::{ValidationIssue, assets::subfolders};
+
+lazy_static! {
+	static ref MODS_FOLDER: std::path::PathBuf = super::ASSETS_FOLDER.parent().unwrap().join("mods");
+	static ref MODS: LoadedMods = load_mods();
+}
+
+/* Files that are merged across mods rather than replaced, so providing them twice isn't a conflict */
+const MERGED_FILES: &[&str] = &["mod.yaml", "data/names.yaml", "data/character_types.yaml"];
+
+struct LoadedMods {
+	mods: Vec<Mod>,
+	issues: Vec<ValidationIssue>,
+}
+
+/*
+	A folder in `mods/` that layers files on top of `assets`, using the same
+	layout (`data/...`, `textures/...`, `themes/...`). Later mods in the load
+	order win when two of them provide the same file.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mod {
+	pub id: String,
+	pub name: String,
+	pub version: String,
+	pub description: String,
+	pub path: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ModManifest {
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	version: Option<String>,
+	#[serde(default)]
+	description: Option<String>,
+}
+
+fn load_mods() -> LoadedMods {
+	let folders = subfolders(&MODS_FOLDER);
+	let mut issues = Vec::new();
+
+	// Without a load order every mod is enabled, alphabetically
+	let load_order_path = MODS_FOLDER.join("load_order.yaml");
+	let load_order: Vec<String> = match std::fs::File::open(&load_order_path) {
+		Ok(file) => match serde_yaml::from_reader(file) {
+			Ok(load_order) => load_order,
+			Err(error) => {
+				issues.push(ValidationIssue::fatal(&load_order_path, None, error.to_string()));
+				Vec::new()
+			}
+		},
+		Err(_) => folders.clone(),
+	};
+
+	for folder in folders.iter().filter(|folder| !load_order.contains(folder)) {
+		issues.push(ValidationIssue::warning(&load_order_path, Some(folder), String::from("not in the load order, the mod is disabled")));
+	}
+
+	let mut mods = Vec::new();
+	for id in load_order {
+		if mods.iter().any(|loaded: &Mod| loaded.id == id) {
+			issues.push(ValidationIssue::warning(&load_order_path, Some(&id), String::from("listed more than once, only the first entry is used")));
+			continue;
+		}
+		if !folders.contains(&id) {
+			issues.push(ValidationIssue::warning(&load_order_path, Some(&id), format!("no such folder in {}", MODS_FOLDER.display())));
+			continue;
+		}
+
+		let path = MODS_FOLDER.join(&id);
+		let manifest_path = path.join("mod.yaml");
+		let manifest: ModManifest = match std::fs::File::open(&manifest_path) {
+			Ok(file) => match serde_yaml::from_reader(file) {
+				Ok(manifest) => manifest,
+				Err(error) => {
+					issues.push(ValidationIssue::fatal(&manifest_path, None, error.to_string()));
+					continue;
+				}
+			},
+			Err(_) => ModManifest::default(),
+		};
+
+		println!("Loading mod {}", id);
+		mods.push(Mod {
+			name: manifest.name.unwrap_or_else(|| id.clone()),
+			version: manifest.version.unwrap_or_else(|| String::from("0.0.0")),
+			description: manifest.description.unwrap_or_default(),
+			path,
+			id,
+		});
+	}
+
+	issues.extend(find_conflicts(&mods));
+	LoadedMods { mods, issues }
+}
+
+fn relative_files(root: &std::path::Path, directory: &std::path::Path, files: &mut Vec<std::path::PathBuf>) {
+	let entries = match std::fs::read_dir(directory) {
+		Ok(entries) => entries,
+		Err(_) => return,
+	};
+	for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+		if path.is_dir() {
+			relative_files(root, &path, files);
+		} else {
+			files.push(path.strip_prefix(root).unwrap().to_path_buf());
+		}
+	}
+}
+
+/* Two mods replacing the same file is allowed, but the one loaded first silently loses, so say so */
+fn find_conflicts(mods: &[Mod]) -> Vec<ValidationIssue> {
+	let mut owners: std::collections::HashMap<std::path::PathBuf, &str> = std::collections::HashMap::new();
+	let mut issues = Vec::new();
+	for loaded in mods {
+		let mut files = Vec::new();
+		relative_files(&loaded.path, &loaded.path, &mut files);
+		files.sort();
+		for file in files {
+			if MERGED_FILES.iter().any(|merged| std::path::Path::new(merged) == file) {
+				continue;
+			}
+			if let Some(previous) = owners.insert(file.clone(), &loaded.id) {
+				issues.push(ValidationIssue::warning(
+					&loaded.path.join(&file),
+					Some(&loaded.id),
+					format!("overrides the same file from mod {}", previous),
+				));
+			}
+		}
+	}
+	issues
+}
+
+impl Mod {
+	pub fn active() -> &'static [Mod] {
+		&MODS.mods
+	}
+
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&MODS.issues
+	}
+
+	/* Identifies the exact mod set a run was played with, e.g. ["hardcore@1.0.0"] */
+	pub fn active_ids() -> Vec<String> {
+		MODS.mods.iter().map(|loaded| format!("{}@{}", loaded.id, loaded.version)).collect()
+	}
+}
@@ -0,0 +1,212 @@
+use lazy_static::lazy_static;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use super::{CharacterStats, CharacterType, assets::{layered_files_with_extension, resolve}};
+
+lazy_static! {
+	static ref NAME_GENERATOR: NameGenerator = {
+		let mut names: Vec<String> = super::character_names().to_vec();
+		for path in layered_files_with_extension("data/name_lists", "yaml") {
+			println!("Loading name list {}", path.display());
+			let list: Vec<String> = serde_yaml::from_reader(std::fs::File::open(&path).unwrap())
+				.unwrap_or_else(|error| panic!("Failed to load {}: {}", path.display(), error));
+			names.extend(list);
+		}
+		NameGenerator::train(&names)
+	};
+
+	static ref ENEMY_TITLES: Vec<EnemyTitle> = {
+		println!("Loading enemy titles");
+		if !super::ASSETS_FOLDER.join("data/enemy_titles.yaml").exists() {
+			init_enemy_titles();
+		}
+		let path = resolve("data/enemy_titles.yaml");
+		let mut titles: Vec<EnemyTitle> = match serde_yaml::from_reader(std::fs::File::open(&path).unwrap()) {
+			Ok(titles) => titles,
+			Err(error) => panic!("Failed to load {}: {}", path.display(), error),
+		};
+		titles.sort_by_key(|title| title.min_strength);
+		titles
+	};
+}
+
+/* How many letters of context the generator looks at when picking the next one */
+const ORDER: usize = 2;
+const MIN_LENGTH: usize = 3;
+const MAX_LENGTH: usize = 10;
+const MAX_ATTEMPTS: usize = 32;
+
+/*
+	A character level Markov chain trained on the player names and the lists in
+	`data/name_lists`. It produces names that sound like the training set
+	without repeating it.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct NameGenerator {
+	transitions: std::collections::HashMap<String, Vec<char>>,
+	starts: Vec<String>,
+	known: std::collections::HashSet<String>,
+}
+
+/* Prefix given to enemies whose stats add up to at least `min_strength` */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyTitle {
+	pub min_strength: i32,
+	pub title: String,
+}
+
+fn init_enemy_titles() {
+	let titles: Vec<EnemyTitle> = [(0, "Feeble"), (9, "Wandering"), (12, "Seasoned"), (15, "Veteran"), (19, "Legendary")]
+		.iter()
+		.map(|&(min_strength, title)| EnemyTitle { min_strength, title: title.to_string() })
+		.collect();
+
+	super::storage::write_document(&super::storage::assets(), "data/enemy_titles.yaml", &titles).unwrap();
+}
+
+impl NameGenerator {
+	pub fn train(names: &[String]) -> NameGenerator {
+		let mut generator = NameGenerator::default();
+		for name in names {
+			let letters: Vec<char> = name.trim().to_lowercase().chars().collect();
+			if letters.len() < ORDER {
+				continue;
+			}
+			generator.known.insert(letters.iter().collect());
+			generator.starts.push(letters[..ORDER].iter().collect());
+
+			// '$' marks the end of a name so the chain learns where names stop
+			let mut padded = letters.clone();
+			padded.push('$');
+			for window in padded.windows(ORDER + 1) {
+				let context: String = window[..ORDER].iter().collect();
+				generator.transitions.entry(context).or_default().push(window[ORDER]);
+			}
+		}
+		generator
+	}
+
+	pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+		if self.starts.is_empty() {
+			return None;
+		}
+
+		let mut fallback = None;
+		for _ in 0..MAX_ATTEMPTS {
+			let mut name: Vec<char> = self.starts[rng.gen_range(0..self.starts.len())].chars().collect();
+			while name.len() < MAX_LENGTH {
+				let context: String = name[name.len() - ORDER..].iter().collect();
+				let next = match self.transitions.get(&context) {
+					Some(next) => next[rng.gen_range(0..next.len())],
+					None => '$',
+				};
+				if next == '$' {
+					break;
+				}
+				name.push(next);
+			}
+
+			let name: String = name.into_iter().collect();
+			if name.chars().count() < MIN_LENGTH {
+				continue;
+			}
+			if !self.known.contains(&name) {
+				return Some(super::assets::title_case(&name));
+			}
+			fallback = Some(super::assets::title_case(&name));
+		}
+		fallback
+	}
+
+	pub fn shared() -> &'static NameGenerator {
+		&NAME_GENERATOR
+	}
+}
+
+impl EnemyTitle {
+	/* The highest title `stats` qualify for */
+	pub fn for_stats(stats: &CharacterStats) -> Option<&'static EnemyTitle> {
+		EnemyTitle::pick(&ENEMY_TITLES, stats)
+	}
+
+	/* `titles` must be sorted by `min_strength` */
+	fn pick<'a>(titles: &'a [EnemyTitle], stats: &CharacterStats) -> Option<&'a EnemyTitle> {
+		let strength = stats.vitality + stats.attack + stats.defense + stats.stamina;
+		titles.iter().rev().find(|title| strength >= title.min_strength)
+	}
+
+	/*
+		"Rico the Veteran Soldier". A role from the spawn table's name pool
+		replaces the strength title and type name.
+	*/
+	pub fn enemy_name<R: Rng + ?Sized>(rng: &mut R, character_type: &CharacterType, stats: &CharacterStats, role: Option<&str>) -> String {
+		let name = match NameGenerator::shared().generate(rng) {
+			Some(name) => name,
+			None => return character_type.enemy_display_name.clone(),
+		};
+		let role = match role {
+			Some(role) => role.to_string(),
+			None => match EnemyTitle::for_stats(stats) {
+				Some(title) if !title.title.is_empty() => format!("{} {}", title.title, character_type.display_name),
+				_ => character_type.display_name.clone(),
+			},
+		};
+		format!("{} the {}", name, role)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{rngs::StdRng, SeedableRng};
+
+	fn names(list: &[&str]) -> Vec<String> {
+		list.iter().map(|name| name.to_string()).collect()
+	}
+
+	#[test]
+	fn generated_names_stay_within_the_length_limits() {
+		let generator = NameGenerator::train(&names(&["Aurelia", "Cassius", "Decimus", "Flavia", "Gaius", "Livia", "Lucius", "Octavia", "Bjorn", "Sigrid"]));
+		let mut rng = StdRng::seed_from_u64(7);
+		for _ in 0..500 {
+			let name = generator.generate(&mut rng).expect("a name");
+			let length = name.chars().count();
+			assert!((MIN_LENGTH..=MAX_LENGTH).contains(&length), "{}", name);
+			assert!(name.chars().next().unwrap().is_uppercase(), "{}", name);
+		}
+	}
+
+	#[test]
+	fn empty_and_short_lists_dont_panic() {
+		let mut rng = StdRng::seed_from_u64(7);
+		assert_eq!(NameGenerator::train(&[]).generate(&mut rng), None);
+
+		// Too short to learn from, or only ever producing names below the minimum length
+		assert_eq!(NameGenerator::train(&names(&["A", "", "  "])).generate(&mut rng), None);
+		assert_eq!(NameGenerator::train(&names(&["Al", "Bo"])).generate(&mut rng), None);
+
+		// A single name can only be repeated, which is still better than nothing
+		assert_eq!(NameGenerator::train(&names(&["Ann"])).generate(&mut rng), Some(String::from("Ann")));
+	}
+
+	#[test]
+	fn titles_follow_enemy_strength() {
+		let titles: Vec<EnemyTitle> = [(5, "Wandering"), (10, "Veteran"), (20, "Legendary")]
+			.iter()
+			.map(|&(min_strength, title)| EnemyTitle { min_strength, title: title.to_string() })
+			.collect();
+		let title = |points: i32| EnemyTitle::pick(&titles, &CharacterStats::new(points, 0, 0, 0)).map(|title| title.title.as_str());
+		assert_eq!(title(4), None);
+		assert_eq!(title(5), Some("Wandering"));
+		assert_eq!(title(19), Some("Veteran"));
+		assert_eq!(title(40), Some("Legendary"));
+
+		// The shipped titles never get weaker as enemies get stronger
+		let mut previous = i32::MIN;
+		for points in 0..40 {
+			let strength = EnemyTitle::for_stats(&CharacterStats::new(points, 1, 1, 1)).map_or(i32::MIN, |title| title.min_strength);
+			assert!(strength >= previous);
+			previous = strength;
+		}
+	}
+}
@@ -0,0 +1,88 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use super::{Affix, CharacterStats, Difficulty, storage};
+
+/* Chance for a spawn after the first to be a nemesis, if one is waiting */
+const NEMESIS_CHANCE: f64 = 0.15;
+/* Extra attribute points a nemesis gets for every hero it has slain */
+const POINTS_PER_KILL: i32 = 2;
+/* Only the most dangerous nemeses are remembered */
+const MAX_NEMESES: usize = 5;
+
+/*
+	An enemy that killed a player. It is saved in `nemeses.yaml` in the user data folder
+	and may come back in a later run on the same difficulty, a little stronger
+	for every kill.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Nemesis {
+	pub name: String,
+	pub character_type: String,
+	pub stats: CharacterStats,
+	#[serde(default)]
+	pub affixes: Vec<Affix>,
+	pub difficulty: Difficulty,
+	pub kills: u32,
+}
+
+impl Nemesis {
+	pub const FILE: &'static str = "nemeses.yaml";
+
+	pub fn new(name: String, character_type: String, stats: CharacterStats, affixes: Vec<Affix>, difficulty: Difficulty) -> Nemesis {
+		Nemesis { name, character_type, stats, affixes, difficulty, kills: 1 }
+	}
+
+	pub fn load_all() -> Vec<Nemesis> {
+		match storage::read_document(&*storage::user_data(), Nemesis::FILE) {
+			Ok(nemeses) => nemeses.unwrap_or_default(),
+			Err(error) => {
+				eprintln!("{}", error);
+				Vec::new()
+			}
+		}
+	}
+
+	fn save_all(nemeses: &[Nemesis]) {
+		if let Err(error) = storage::write_document(&*storage::user_data(), Nemesis::FILE, nemeses) {
+			eprintln!("{}", error);
+		}
+	}
+
+	/* Remembers `killer`, or counts another kill if it already was a nemesis */
+	pub fn record_kill(killer: Nemesis) {
+		let mut nemeses = Nemesis::load_all();
+		match nemeses.iter_mut().find(|nemesis| nemesis.name == killer.name) {
+			Some(nemesis) => nemesis.kills += 1,
+			None => nemeses.push(killer),
+		}
+
+		nemeses.sort_by(|a, b| b.kills.cmp(&a.kills));
+		nemeses.truncate(MAX_NEMESES);
+		Nemesis::save_all(&nemeses);
+	}
+
+	/* Forgets the nemesis called `name` once it has been defeated */
+	pub fn record_defeat(name: &str) {
+		let mut nemeses = Nemesis::load_all();
+		nemeses.retain(|nemesis| nemesis.name != name);
+		Nemesis::save_all(&nemeses);
+	}
+
+	/* Maybe brings a nemesis back for this spawn */
+	pub fn maybe_return<R: Rng + ?Sized>(rng: &mut R, difficulty: &Difficulty, kills: u32) -> Option<Nemesis> {
+		if kills == 0 || !rng.gen_bool(NEMESIS_CHANCE) {
+			return None;
+		}
+
+		let candidates: Vec<Nemesis> = Nemesis::load_all().into_iter().filter(|nemesis| &nemesis.difficulty == difficulty).collect();
+		match candidates.is_empty() {
+			true => None,
+			false => Some(candidates[rng.gen_range(0..candidates.len())].clone()),
+		}
+	}
+
+	/* The saved stats plus a bonus for every hero slain */
+	pub fn empowered_stats(&self, rng: &mut rand::prelude::ThreadRng) -> CharacterStats {
+		self.stats + CharacterStats::random(rng, POINTS_PER_KILL * self.kills as i32)
+	}
+}
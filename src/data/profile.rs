@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use super::{Difficulty, storage};
+
+/* Long-term records that outlive a single run, saved in `profile.yaml` in the user data folder */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+	#[serde(default)]
+	pub nemeses_defeated: Vec<DefeatedNemesis>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefeatedNemesis {
+	pub name: String,
+	/* How many heroes it had slain before it fell */
+	pub kills: u32,
+	pub defeated_by: String,
+	pub difficulty: Difficulty,
+}
+
+impl Profile {
+	pub const FILE: &'static str = "profile.yaml";
+
+	pub fn load() -> Profile {
+		match storage::read_document(&*storage::user_data(), Profile::FILE) {
+			Ok(profile) => profile.unwrap_or_default(),
+			Err(error) => {
+				eprintln!("{}", error);
+				Profile::default()
+			}
+		}
+	}
+
+	pub fn save(&self) {
+		if let Err(error) = storage::write_document(&*storage::user_data(), Profile::FILE, self) {
+			eprintln!("{}", error);
+		}
+	}
+
+	pub fn record_nemesis_defeat(defeated: DefeatedNemesis) {
+		let mut profile = Profile::load();
+		profile.nemeses_defeated.push(defeated);
+		profile.save();
+	}
+}
@@ -0,0 +1,333 @@
+/*
+	Every finished run, kept in `history.sqlite3` in the user data folder when
+	the game is built with the `sqlite` feature. The leaderboard is a view over
+	it, so nothing below the top ten is ever lost.
+*/
+
+use rusqlite::{params, Connection};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use super::{Difficulty, Leaderboard, LeaderboardEntry, save_slot::time_ago_text, storage};
+
+const FILE: &str = "history.sqlite3";
+/* Bump this and add a step to `upgrade` whenever the tables change */
+const SCHEMA_VERSION: i64 = 3;
+
+lazy_static! {
+	static ref CONNECTION: Mutex<Connection> = Mutex::new(open());
+}
+
+/* Narrows down a query, every field left as `None` matches any run */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunFilter {
+	pub difficulty: Option<Difficulty>,
+	pub character_type: Option<String>,
+	/* Seconds since the unix epoch */
+	pub finished_after: Option<u64>,
+	pub finished_before: Option<u64>,
+	/* The exact mod set the run was played with */
+	pub mods: Option<Vec<String>>,
+}
+
+pub struct RunHistory;
+
+/* A database that can't be opened or upgraded, e.g. a corrupt one, is left alone for this session */
+fn open() -> Connection {
+	let path = super::USER_DATA_FOLDER.join(FILE);
+	let opened = Connection::open(&path).and_then(|mut connection| upgrade(&mut connection).map(|_| connection));
+	match opened {
+		Ok(connection) => connection,
+		Err(error) => {
+			eprintln!("Failed to open {}, runs won't be kept after this session: {}", path.display(), error);
+			let mut connection = Connection::open_in_memory().unwrap();
+			if let Err(error) = upgrade(&mut connection) {
+				eprintln!("Failed to set up the run history: {}", error);
+			}
+			connection
+		}
+	}
+}
+
+/*
+	Creates or updates the tables, importing `leaderboard.yaml` into a new
+	database. All of it happens in one transaction, so a failed upgrade leaves
+	the database as it was and is simply tried again on the next start.
+*/
+fn upgrade(connection: &mut Connection) -> rusqlite::Result<()> {
+	let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+	if version >= SCHEMA_VERSION {
+		return Ok(());
+	}
+	let connection = connection.transaction()?;
+
+	if version < 1 {
+		connection.execute_batch(
+			"CREATE TABLE IF NOT EXISTS runs (
+				id INTEGER PRIMARY KEY,
+				name TEXT NOT NULL,
+				character_type TEXT NOT NULL,
+				score INTEGER NOT NULL,
+				kills INTEGER NOT NULL,
+				difficulty TEXT NOT NULL,
+				custom INTEGER NOT NULL,
+				modifiers TEXT NOT NULL,
+				score_multiplier REAL NOT NULL,
+				mods TEXT NOT NULL,
+				finished_at INTEGER NOT NULL
+			);
+			CREATE INDEX IF NOT EXISTS runs_by_difficulty ON runs (difficulty);
+			CREATE INDEX IF NOT EXISTS runs_by_finished_at ON runs (finished_at);",
+		)?;
+	}
+	if version < 2 {
+		connection.execute_batch(
+			"ALTER TABLE runs ADD COLUMN seed INTEGER NOT NULL DEFAULT 0;
+			ALTER TABLE runs ADD COLUMN replay_hash TEXT NOT NULL DEFAULT '';
+			ALTER TABLE runs ADD COLUMN signature TEXT NOT NULL DEFAULT '';",
+		)?;
+	}
+	if version < 3 {
+		// Whether the signature matched when the run was recorded, so top runs can be picked in SQL
+		connection.execute_batch("ALTER TABLE runs ADD COLUMN verified INTEGER NOT NULL DEFAULT 0;")?;
+	}
+
+	if version < 1 {
+		match storage::read_document::<Leaderboard>(&*storage::user_data(), Leaderboard::FILE) {
+			Ok(Some(leaderboard)) => {
+				println!("Importing {} leaderboard entries into {}", leaderboard.entries.len(), FILE);
+				for entry in &leaderboard.entries {
+					insert(&connection, entry)?;
+				}
+			}
+			Ok(None) => {}
+			Err(error) => eprintln!("{}, it was not imported", error),
+		}
+	} else if version < 3 {
+		let runs = {
+			let mut statement = connection.prepare(&format!("SELECT id, {} FROM runs", COLUMNS))?;
+			let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, entry_from(row, 1)?)))?;
+			rows.collect::<rusqlite::Result<Vec<_>>>()?
+		};
+		for (id, entry) in runs {
+			connection.execute("UPDATE runs SET verified = ?1 WHERE id = ?2", params![entry.is_verified(), id])?;
+		}
+	}
+
+	connection.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+	connection.commit()
+}
+
+fn insert(connection: &Connection, entry: &LeaderboardEntry) -> rusqlite::Result<()> {
+	connection.execute(
+		"INSERT INTO runs (name, character_type, score, kills, difficulty, custom, modifiers, score_multiplier, mods, finished_at, seed, replay_hash, signature, verified)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+		params![
+			entry.name,
+			entry.character_type,
+			entry.score,
+			entry.kills,
+			entry.difficulty.as_str(),
+			entry.custom,
+			entry.modifiers.join("\n"),
+			entry.score_multiplier,
+			entry.mods.join("\n"),
+			entry.finished_at as i64,
+			entry.seed as i64,
+			entry.replay_hash,
+			entry.signature,
+			entry.is_verified(),
+		],
+	)?;
+	Ok(())
+}
+
+impl LeaderboardEntry {
+	/* "5 days ago" */
+	pub fn finished_ago_text(&self) -> String {
+		match self.finished_at {
+			0 => String::from("before history was kept"),
+			time => time_ago_text(time),
+		}
+	}
+}
+
+/* The columns `entry` reads, in order */
+const COLUMNS: &str = "name, character_type, score, kills, difficulty, custom, modifiers, score_multiplier, mods, finished_at, seed, replay_hash, signature";
+
+fn entry(row: &rusqlite::Row) -> rusqlite::Result<LeaderboardEntry> {
+	entry_from(row, 0)
+}
+
+/* Reads `COLUMNS` starting at column `first`, checking the signature again rather than trusting the `verified` column */
+fn entry_from(row: &rusqlite::Row, first: usize) -> rusqlite::Result<LeaderboardEntry> {
+	Ok(LeaderboardEntry {
+		name: row.get(first)?,
+		character_type: row.get(first + 1)?,
+		score: row.get(first + 2)?,
+		kills: row.get(first + 3)?,
+		difficulty: Difficulty::from(row.get::<_, String>(first + 4)?),
+		custom: row.get(first + 5)?,
+		modifiers: split(row.get(first + 6)?),
+		score_multiplier: row.get(first + 7)?,
+		mods: split(row.get(first + 8)?),
+		finished_at: row.get::<_, i64>(first + 9)? as u64,
+		seed: row.get::<_, i64>(first + 10)? as u64,
+		replay_hash: row.get(first + 11)?,
+		signature: row.get(first + 12)?,
+		verified: false,
+	}
+	.checked())
+}
+
+fn split(list: String) -> Vec<String> {
+	match list.is_empty() {
+		true => Vec::new(),
+		false => list.split('\n').map(String::from).collect(),
+	}
+}
+
+impl RunHistory {
+	pub fn record(entry: &LeaderboardEntry) {
+		if let Err(error) = insert(&CONNECTION.lock().unwrap(), entry) {
+			eprintln!("Failed to record the run in {}: {}", FILE, error);
+		}
+	}
+
+	/* Every run matching `filter`, best first as the leaderboard ranks them */
+	pub fn query(filter: &RunFilter) -> Vec<LeaderboardEntry> {
+		RunHistory::ranked(filter, None)
+	}
+
+	/* Enough runs to fill a leaderboard of `count` entries per mod set */
+	pub fn leaderboard(count: usize) -> Vec<LeaderboardEntry> {
+		RunHistory::ranked(&RunFilter::default(), Some(count))
+	}
+
+	/* The best `count` runs matching `filter` */
+	pub fn top(count: usize, filter: &RunFilter) -> Vec<LeaderboardEntry> {
+		let mut entries = RunHistory::ranked(filter, Some(count));
+		entries.truncate(count);
+		entries
+	}
+
+	/* Forgets every run, used by Erase Data */
+	pub fn clear() {
+		if let Err(error) = CONNECTION.lock().unwrap().execute("DELETE FROM runs", []) {
+			eprintln!("Failed to clear {}: {}", FILE, error);
+		}
+	}
+
+	fn ranked(filter: &RunFilter, limit: Option<usize>) -> Vec<LeaderboardEntry> {
+		let connection = CONNECTION.lock().unwrap();
+		let mut entries = match RunHistory::select(&connection, filter, limit) {
+			Ok(entries) => entries,
+			Err(error) => {
+				eprintln!("Failed to read {}: {}", FILE, error);
+				Vec::new()
+			}
+		};
+		entries.sort_by(|a, b| b.cmp(a));
+		entries
+	}
+
+	/*
+		The runs matching `filter`, at most `limit` of every mod set, custom flag
+		and difficulty. The leaderboard ranks verified runs first and by
+		difficulty before score, so the best `limit` of each of those groups
+		always holds the best `limit` runs overall, and the rest is never read.
+	*/
+	fn select(connection: &Connection, filter: &RunFilter, limit: Option<usize>) -> rusqlite::Result<Vec<LeaderboardEntry>> {
+		let mods = filter.mods.as_ref().map(|mods| mods.join("\n"));
+		let mut statement = connection.prepare(&format!(
+			"SELECT {} FROM (
+				SELECT *, ROW_NUMBER() OVER (PARTITION BY mods, custom, difficulty ORDER BY verified DESC, score DESC, finished_at) AS place
+				FROM runs
+				WHERE (?1 IS NULL OR difficulty = ?1)
+					AND (?2 IS NULL OR character_type = ?2)
+					AND (?3 IS NULL OR finished_at >= ?3)
+					AND (?4 IS NULL OR finished_at < ?4)
+					AND (?5 IS NULL OR mods = ?5)
+			)
+			WHERE ?6 IS NULL OR place <= ?6",
+			COLUMNS,
+		))?;
+		let rows = statement.query_map(
+			params![
+				filter.difficulty.as_ref().map(|difficulty| difficulty.as_str()),
+				filter.character_type,
+				filter.finished_after.map(|time| time as i64),
+				filter.finished_before.map(|time| time as i64),
+				mods,
+				limit.map(|limit| limit as i64),
+			],
+			entry,
+		)?;
+		rows.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::storage::with_memory_storage;
+
+	fn history() -> Connection {
+		let mut connection = Connection::open_in_memory().unwrap();
+		upgrade(&mut connection).unwrap();
+		connection
+	}
+
+	fn run(name: &str, score: u32) -> LeaderboardEntry {
+		LeaderboardEntry::new(String::from(name), score, Difficulty::from(String::from("easy")), false)
+	}
+
+	#[test]
+	fn unsigned_high_scores_dont_push_verified_runs_off_the_board() {
+		with_memory_storage(|| {
+			let connection = history();
+			for _ in 0..10 {
+				insert(&connection, &run("Cheater", 9999)).unwrap();
+			}
+			insert(&connection, &run("Robby", 3).signed()).unwrap();
+
+			let runs = RunHistory::select(&connection, &RunFilter::default(), Some(10)).unwrap();
+			let leaderboard = Leaderboard::from_runs(10, runs);
+			assert_eq!(leaderboard.entries.len(), 10);
+			assert_eq!(leaderboard.entries[0].name, "Robby");
+			assert!(leaderboard.entries[0].is_verified());
+			assert!(leaderboard.entries[1..].iter().all(|entry| !entry.is_verified()));
+		});
+	}
+
+	#[test]
+	fn select_keeps_the_best_of_every_group() {
+		with_memory_storage(|| {
+			let connection = history();
+			for score in 1..=5 {
+				insert(&connection, &run("Easy", score).signed()).unwrap();
+				insert(&connection, &LeaderboardEntry::new(String::from("Hard"), score, Difficulty::from(String::from("hard")), false).signed()).unwrap();
+			}
+
+			let runs = RunHistory::select(&connection, &RunFilter::default(), Some(2)).unwrap();
+			assert_eq!(runs.len(), 4);
+			assert!(runs.iter().all(|entry| entry.score >= 4));
+
+			let filter = RunFilter { difficulty: Some(Difficulty::from(String::from("hard"))), ..RunFilter::default() };
+			let runs = RunHistory::select(&connection, &filter, None).unwrap();
+			assert_eq!(runs.len(), 5);
+			assert!(runs.iter().all(|entry| entry.name == "Hard"));
+		});
+	}
+
+	#[test]
+	fn upgrading_again_does_nothing() {
+		with_memory_storage(|| {
+			let mut connection = history();
+			insert(&connection, &run("Robby", 3).signed()).unwrap();
+			upgrade(&mut connection).unwrap();
+			let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+			assert_eq!(version, SCHEMA_VERSION);
+			assert_eq!(RunHistory::select(&connection, &RunFilter::default(), None).unwrap().len(), 1);
+		});
+	}
+}
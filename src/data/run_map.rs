@@ -0,0 +1,161 @@
+use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/* Layers between the opening battle and the boss */
+const MIDDLE_LAYERS: usize = 6;
+const MIN_NODES_PER_LAYER: usize = 2;
+const MAX_NODES_PER_LAYER: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+	Battle,
+	Elite,
+	Shop,
+	Rest,
+	Event,
+	Boss,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapNode {
+	pub kind: NodeKind,
+	/* Indices of the nodes in the next layer this one leads to */
+	pub next: Vec<usize>,
+}
+
+/*
+	The branching path through one act of a run. The same seed always gives
+	the same layout; `position` is the (layer, node) the player is standing on.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMap {
+	pub seed: u64,
+	pub act: u32,
+	pub layers: Vec<Vec<MapNode>>,
+	pub position: (usize, usize),
+	/* Every node visited so far, oldest first */
+	#[serde(default)]
+	pub path: Vec<(usize, usize)>,
+}
+
+impl NodeKind {
+	pub fn name(&self) -> &'static str {
+		match self {
+			NodeKind::Battle => "Battle",
+			NodeKind::Elite => "Elite",
+			NodeKind::Shop => "Shop",
+			NodeKind::Rest => "Rest",
+			NodeKind::Event => "Event",
+			NodeKind::Boss => "Boss",
+		}
+	}
+
+	pub fn is_battle(&self) -> bool {
+		matches!(self, NodeKind::Battle | NodeKind::Elite | NodeKind::Boss)
+	}
+}
+
+impl RunMap {
+	pub fn generate(seed: u64, act: u32) -> RunMap {
+		let mut rng = StdRng::seed_from_u64(seed);
+
+		let mut kinds: Vec<Vec<NodeKind>> = vec![vec![NodeKind::Battle]];
+		for layer in 1..=MIDDLE_LAYERS {
+			let count = rng.gen_range(MIN_NODES_PER_LAYER..=MAX_NODES_PER_LAYER);
+			kinds.push((0..count).map(|_| RunMap::random_kind(&mut rng, layer)).collect());
+		}
+		kinds.push(vec![NodeKind::Boss]);
+
+		let mut layers: Vec<Vec<MapNode>> = Vec::new();
+		for (layer, layer_kinds) in kinds.iter().enumerate() {
+			let next_count = kinds.get(layer + 1).map_or(0, |next| next.len());
+			let mut nodes: Vec<MapNode> = layer_kinds
+				.iter()
+				.enumerate()
+				.map(|(i, &kind)| MapNode { kind, next: RunMap::random_edges(&mut rng, i, layer_kinds.len(), next_count) })
+				.collect();
+
+			// Every node in the next layer has to be reachable from somewhere
+			for target in 0..next_count {
+				if !nodes.iter().any(|node| node.next.contains(&target)) {
+					let closest = RunMap::closest_index(target, next_count, nodes.len());
+					nodes[closest].next.push(target);
+					nodes[closest].next.sort_unstable();
+				}
+			}
+			layers.push(nodes);
+		}
+
+		RunMap { seed, act, layers, position: (0, 0), path: vec![(0, 0)] }
+	}
+
+	/* Rest, shops and elites stay out of the first layer so the run warms up first */
+	fn random_kind<R: Rng>(rng: &mut R, layer: usize) -> NodeKind {
+		let weights: [(NodeKind, u32); 5] = match layer {
+			1 => [(NodeKind::Battle, 70), (NodeKind::Event, 30), (NodeKind::Elite, 0), (NodeKind::Rest, 0), (NodeKind::Shop, 0)],
+			_ => [(NodeKind::Battle, 45), (NodeKind::Event, 18), (NodeKind::Elite, 15), (NodeKind::Rest, 12), (NodeKind::Shop, 10)],
+		};
+		let distribution = WeightedIndex::new(weights.iter().map(|(_, weight)| *weight)).unwrap();
+		weights[distribution.sample(rng)].0
+	}
+
+	/* Links node `index` to one or two nodes lined up with it in the next layer */
+	fn random_edges<R: Rng>(rng: &mut R, index: usize, count: usize, next_count: usize) -> Vec<usize> {
+		if next_count == 0 {
+			return Vec::new();
+		}
+		let closest = RunMap::closest_index(index, count, next_count);
+		let mut edges = vec![closest];
+		if next_count > 1 && rng.gen_bool(0.5) {
+			let neighbour = match closest {
+				0 => 1,
+				_ if closest == next_count - 1 => closest - 1,
+				_ if rng.gen_bool(0.5) => closest - 1,
+				_ => closest + 1,
+			};
+			edges.push(neighbour);
+		}
+		edges.sort_unstable();
+		edges
+	}
+
+	fn closest_index(index: usize, count: usize, other_count: usize) -> usize {
+		match count {
+			0 | 1 => other_count / 2,
+			_ => ((index as f64 / (count - 1) as f64) * (other_count - 1) as f64).round() as usize,
+		}
+	}
+
+	pub fn current(&self) -> &MapNode {
+		&self.layers[self.position.0][self.position.1]
+	}
+
+	/* The (layer, node) positions the player can move to next */
+	pub fn choices(&self) -> Vec<(usize, usize)> {
+		let layer = self.position.0 + 1;
+		self.current().next.iter().map(|&node| (layer, node)).collect()
+	}
+
+	/* How far into the whole run the player is, counting the layers of earlier acts */
+	pub fn depth(&self) -> u32 {
+		((self.act - 1) as usize * self.layers.len() + self.position.0) as u32
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.position.0 + 1 >= self.layers.len()
+	}
+
+	pub fn move_to(&mut self, position: (usize, usize)) -> Result<NodeKind, String> {
+		if !self.choices().contains(&position) {
+			return Err(format!("Can't move from {:?} to {:?}", self.position, position));
+		}
+		self.position = position;
+		self.path.push(position);
+		Ok(self.current().kind)
+	}
+
+	/* The map for the act after this one's boss */
+	pub fn next_act(&self) -> RunMap {
+		RunMap::generate(self.seed.wrapping_add(1), self.act + 1)
+	}
+}
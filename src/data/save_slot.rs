@@ -0,0 +1,469 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use super::{Difficulty, Format, format::save_format, integrity, storage, migrations::{self, CURRENT_SAVE_VERSION}};
+use serde_yaml::Value;
+
+pub const SAVE_SLOT_COUNT: usize = 5;
+
+/* What the save browser shows about a slot without loading the whole run */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+	pub name: String,
+	pub character_name: String,
+	pub character_type: String,
+	pub difficulty: Difficulty,
+	#[serde(default)]
+	pub custom: bool,
+	pub kills: u32,
+	pub playtime_secs: u64,
+	/* Seconds since the unix epoch */
+	pub saved_at: u64,
+	/* Tells saves of the same run apart from others, older saves get one when they are upgraded */
+	#[serde(default)]
+	pub run_id: u64,
+	/* Set on every save of a run once it ends, so it can't be continued */
+	#[serde(default)]
+	pub finished: bool,
+	/* Goes up by one with every autosave, `saved_at` can't tell two autosaves in the same second apart */
+	#[serde(default)]
+	pub autosave_number: u64,
+}
+
+/* One of the numbered files in `saves` in the user data folder, empty if nothing was saved there */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSlot {
+	pub index: usize,
+	pub metadata: Option<SaveMetadata>,
+	/* Why a save in this slot can't be read, e.g. it was made by a newer game */
+	pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SaveFileRef<'a, T> {
+	version: u64,
+	metadata: &'a SaveMetadata,
+	game: &'a T,
+}
+
+#[derive(Deserialize)]
+struct SaveFile<T> {
+	game: T,
+}
+
+/* Only the metadata, so listing the slots doesn't parse every run */
+#[derive(Deserialize)]
+struct SaveHeader {
+	metadata: SaveMetadata,
+}
+
+impl SaveMetadata {
+	pub fn default_name(index: usize) -> String {
+		format!("Slot {}", index + 1)
+	}
+
+	/* "1:02:03" */
+	pub fn playtime_text(&self) -> String {
+		format!("{}:{:02}:{:02}", self.playtime_secs / 3600, self.playtime_secs / 60 % 60, self.playtime_secs % 60)
+	}
+
+	pub fn saved_ago_text(&self) -> String {
+		time_ago_text(self.saved_at)
+	}
+}
+
+impl SaveSlot {
+	/* The slot's file name without an extension, it can be saved in any format */
+	fn stem(index: usize) -> String {
+		format!("saves/slot_{}", index + 1)
+	}
+
+	pub fn all() -> Vec<SaveSlot> {
+		(0..SAVE_SLOT_COUNT)
+			.map(|index| match SaveSlot::read_metadata(index) {
+				Ok(metadata) => SaveSlot { index, metadata, error: None },
+				Err(error) => SaveSlot { index, metadata: None, error: Some(error) },
+			})
+			.collect()
+	}
+
+	pub fn is_used(&self) -> bool {
+		self.metadata.is_some() || self.error.is_some()
+	}
+
+	pub fn any_used() -> bool {
+		(0..SAVE_SLOT_COUNT).any(|index| existing_file(&SaveSlot::stem(index)).is_some())
+	}
+
+	/* Saves that can't be read still count, so the browser can say why */
+	pub fn is_resumable(&self) -> bool {
+		match &self.metadata {
+			Some(metadata) => !metadata.finished,
+			None => self.error.is_some(),
+		}
+	}
+
+	/* Whether Continue has anything to offer */
+	pub fn any_resumable() -> bool {
+		SaveSlot::all().iter().any(SaveSlot::is_resumable)
+	}
+
+	/* Marks every save of the run `run_id` as finished, called when the run ends */
+	pub fn finish_run(run_id: u64) {
+		for slot in SaveSlot::all() {
+			match &slot.metadata {
+				Some(metadata) if metadata.run_id == run_id && !metadata.finished => {}
+				_ => continue,
+			}
+			let label = SaveMetadata::default_name(slot.index);
+			if let Some(file) = existing_file(&SaveSlot::stem(slot.index)) {
+				if let Err(error) = mark_finished(&file, &label) {
+					eprintln!("{} can't be marked as finished: {}", label, error);
+				}
+			}
+		}
+	}
+
+	fn read_metadata(index: usize) -> Result<Option<SaveMetadata>, String> {
+		match existing_file(&SaveSlot::stem(index)) {
+			Some(file) => read_metadata(&file, &SaveMetadata::default_name(index)),
+			None => Ok(None),
+		}
+	}
+
+	/* Saves `game` to slot `index`, replacing whatever was there */
+	pub fn write<T: Serialize>(index: usize, metadata: &SaveMetadata, game: &T) -> Result<(), String> {
+		write_save(&SaveSlot::stem(index), metadata, game)
+	}
+
+	/* The run in slot `index`, and whether the save is exactly what the game wrote */
+	pub fn read<T: DeserializeOwned>(index: usize) -> Result<(T, bool), String> {
+		let label = SaveMetadata::default_name(index);
+		let file = existing_file(&SaveSlot::stem(index)).ok_or_else(|| format!("{} is empty", label))?;
+		read_game(&file, &label)?.ok_or_else(|| format!("{} is empty", label))
+	}
+
+	/*
+		Moves the single `current_game.yaml` from before save slots existed
+		into the first free slot. It is upgraded when it's loaded like any
+		other old save.
+	*/
+	pub fn import_legacy() {
+		const LEGACY_FILE: &str = "current_game.yaml";
+		let storage = storage::user_data();
+		let legacy = match storage.read(LEGACY_FILE) {
+			Ok(Some(legacy)) => legacy,
+			Ok(None) => return,
+			Err(error) => {
+				eprintln!("{}", error);
+				return;
+			}
+		};
+		let index = match SaveSlot::all().into_iter().find(|slot| !slot.is_used()) {
+			Some(slot) => slot.index,
+			None => {
+				eprintln!("Every save slot is in use, {} was left where it is", storage.location(LEGACY_FILE));
+				return;
+			}
+		};
+
+		match storage.write(&format!("{}.yaml", SaveSlot::stem(index)), &legacy) {
+			Ok(()) => match storage.delete(LEGACY_FILE) {
+				Ok(()) => println!("Moved {} to save slot {}", storage.location(LEGACY_FILE), index + 1),
+				Err(error) => eprintln!("Copied {} to save slot {}, but it couldn't be removed: {}", storage.location(LEGACY_FILE), index + 1, error),
+			},
+			Err(error) => eprintln!("Failed to move {} to save slot {}: {}", storage.location(LEGACY_FILE), index + 1, error),
+		}
+	}
+
+	pub fn delete(index: usize) -> Result<(), String> {
+		delete_save(&SaveSlot::stem(index))
+	}
+
+	/* Removes every slot and autosave, used by Erase Data */
+	pub fn delete_all() -> Result<(), String> {
+		let storage = storage::user_data();
+		let results: Vec<_> = storage.list("saves").iter().map(|file| storage.delete(file)).collect();
+		results.into_iter().collect()
+	}
+}
+
+/*
+	The rotating pair of files written after every turn. Each autosave goes to
+	the older of the two, so a crash while writing still leaves the previous
+	turn to recover from.
+*/
+pub struct Autosave;
+
+impl Autosave {
+	const STEMS: [&'static str; 2] = ["saves/autosave_1", "saves/autosave_2"];
+
+	/* The readable autosaves' files with their metadata, newest first */
+	fn saves() -> Vec<(String, SaveMetadata)> {
+		let mut saves: Vec<_> = Autosave::STEMS
+			.iter()
+			.filter_map(|stem| existing_file(stem))
+			.filter_map(|file| match read_metadata(&file, "The autosave") {
+				Ok(metadata) => metadata.map(|metadata| (file, metadata)),
+				Err(error) => {
+					eprintln!("{}", error);
+					None
+				}
+			})
+			.collect();
+		saves.sort_by(|(_, a), (_, b)| (b.autosave_number, b.saved_at).cmp(&(a.autosave_number, a.saved_at)));
+		saves
+	}
+
+	pub fn write<T: Serialize>(metadata: &SaveMetadata, game: &T) -> Result<(), String> {
+		let newest = Autosave::saves().into_iter().next();
+		let stem = Autosave::STEMS.iter().find(|stem| newest.as_ref().map_or(true, |(newest, _)| !newest.starts_with(*stem))).unwrap();
+		let autosave_number = newest.map_or(1, |(_, newest)| newest.autosave_number + 1);
+		write_save(stem, &SaveMetadata { autosave_number, ..metadata.clone() }, game)
+	}
+
+	/* Loads the newest autosave, falling back to the older one if it can't be read */
+	pub fn read<T: DeserializeOwned>() -> Result<(T, bool), String> {
+		let mut last_error = String::from("There is no autosave to recover");
+		for (file, _) in Autosave::saves() {
+			match read_game(&file, "The autosave") {
+				Ok(Some(game)) => return Ok(game),
+				Ok(None) => {}
+				Err(error) => last_error = error,
+			}
+		}
+		Err(last_error)
+	}
+
+	/*
+		The autosave of a run that never finished, if it is newer than every
+		manual save of the same run
+	*/
+	pub fn recoverable() -> Option<SaveMetadata> {
+		let (_, autosave) = Autosave::saves().into_iter().next()?;
+		let saved_manually = SaveSlot::all()
+			.iter()
+			.filter_map(|slot| slot.metadata.as_ref())
+			.any(|metadata| metadata.run_id == autosave.run_id && metadata.saved_at >= autosave.saved_at);
+		match saved_manually {
+			true => None,
+			false => Some(autosave),
+		}
+	}
+
+	/* Called once a run is saved by hand or over, there is nothing left to recover */
+	pub fn clear() -> Result<(), String> {
+		let results: Vec<_> = Autosave::STEMS.iter().map(|stem| delete_save(stem)).collect();
+		results.into_iter().collect()
+	}
+}
+
+/* The file a save called `stem` was written to, in whichever format that was */
+fn existing_file(stem: &str) -> Option<String> {
+	let storage = storage::user_data();
+	Format::ALL
+		.iter()
+		.map(|format| format!("{}.{}", stem, format.extension()))
+		.find(|file| storage.exists(file))
+}
+
+/* Deletes the save in every format, the first error is returned once all were tried */
+fn delete_save(stem: &str) -> Result<(), String> {
+	let storage = storage::user_data();
+	let results: Vec<_> = Format::ALL.iter().map(|format| storage.delete(&format!("{}.{}", stem, format.extension()))).collect();
+	results.into_iter().collect()
+}
+
+/*
+	The bytes a save's signature covers: the document as written, before any
+	upgrade, so a signed save stays verified after the save format changes.
+*/
+fn signed_contents(document: &Value) -> Result<Vec<u8>, String> {
+	Format::MessagePack.encode_document(document)
+}
+
+/* Writes the save in the current save format, replacing one written in any other */
+fn write_save<T: Serialize>(stem: &str, metadata: &SaveMetadata, game: &T) -> Result<(), String> {
+	let storage = storage::user_data();
+	let format = save_format();
+	let file = format!("{}.{}", stem, format.extension());
+	let metadata = SaveMetadata { saved_at: now(), ..metadata.clone() };
+
+	let document = serde_yaml::to_value(&SaveFileRef { version: CURRENT_SAVE_VERSION, metadata: &metadata, game })
+		.map_err(|error| format!("Failed to write {}: {}", storage.location(&file), error))?;
+	write_signed(&file, document, true)?;
+
+	// A copy left in another format would be read instead of this one
+	for other in Format::ALL.iter().filter(|other| **other != format) {
+		storage.delete(&format!("{}.{}", stem, other.extension()))?;
+	}
+	Ok(())
+}
+
+/* Writes a save's document, signed unless it was loaded from a save that failed verification */
+fn write_signed(file: &str, mut document: Value, sign: bool) -> Result<(), String> {
+	if sign {
+		let signature = integrity::sign(&signed_contents(&document)?);
+		if let Value::Mapping(mapping) = &mut document {
+			mapping.insert(Value::from("signature"), Value::from(signature));
+		}
+	}
+	storage::write_document(&*storage::user_data(), file, &document)
+}
+
+/* Rewrites the save in `file` with its run marked as finished, in the same format */
+fn mark_finished(file: &str, label: &str) -> Result<(), String> {
+	let format = Format::of(file)?;
+	let bytes = storage::user_data().read(file)?.ok_or_else(|| format!("{} is missing", label))?;
+	let mut document = format.decode_document(&bytes)?;
+	let verified = verify(&mut document);
+	let mut document = migrations::migrate(document)?;
+	match document.get_mut("metadata") {
+		Some(Value::Mapping(metadata)) => { metadata.insert(Value::from("finished"), Value::from(true)); }
+		_ => return Err(format!("{} has no details to mark", label)),
+	}
+	write_signed(file, document, verified)
+}
+
+/* Takes the signature out of a save's document and checks it against the rest */
+fn verify(document: &mut Value) -> bool {
+	let signature = match document {
+		Value::Mapping(mapping) => mapping.remove(&Value::from("signature")),
+		_ => None,
+	};
+	match signature {
+		Some(Value::String(signature)) => signed_contents(document).map_or(false, |contents| integrity::verify(&contents, &signature)),
+		_ => false,
+	}
+}
+
+/*
+	The save in `file` upgraded to the current version and read as `T`, or
+	`None` if there is none. `label` names it in errors, e.g. "Slot 2". The
+	flag is false for saves that were changed outside the game or are older
+	than signing.
+*/
+fn read_save<T: DeserializeOwned>(file: &str, label: &str, mismatch: &str) -> Result<Option<(T, bool)>, String> {
+	let format = Format::of(file)?;
+	storage::read_with(&*storage::user_data(), file, |bytes| {
+		let mut document = format.decode_document(bytes).map_err(|error| format!("{} is not a valid save: {}", label, error))?;
+		let verified = verify(&mut document);
+		let document = migrations::migrate(document).map_err(|error| format!("{} can't be loaded. {}", label, error))?;
+		let save = serde_yaml::from_value(document).map_err(|error| format!("{} {}: {}", label, mismatch, error))?;
+		Ok((save, verified))
+	})
+}
+
+fn read_metadata(file: &str, label: &str) -> Result<Option<SaveMetadata>, String> {
+	read_save::<SaveHeader>(file, label, "has unreadable details").map(|header| header.map(|(header, _)| header.metadata))
+}
+
+fn read_game<T: DeserializeOwned>(file: &str, label: &str) -> Result<Option<(T, bool)>, String> {
+	let save = read_save::<SaveFile<T>>(file, label, "can't be loaded, the run doesn't match this version of the game")?;
+	Ok(save.map(|(save, verified)| {
+		if !verified {
+			eprintln!("{} was changed outside the game or is from an older version, scores from this run won't be verified", label);
+		}
+		(save.game, verified)
+	}))
+}
+
+/* "5 minutes ago" for a time in seconds since the unix epoch */
+pub fn time_ago_text(time: u64) -> String {
+	let seconds = now().saturating_sub(time);
+	let (amount, unit) = match seconds {
+		0..=59 => return String::from("just now"),
+		60..=3599 => (seconds / 60, "minute"),
+		3600..=86399 => (seconds / 3600, "hour"),
+		_ => (seconds / 86400, "day"),
+	};
+	format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/* Seconds since the unix epoch */
+pub fn now() -> u64 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{format::set_save_format, storage::with_memory_storage};
+
+	fn metadata(run_id: u64) -> SaveMetadata {
+		SaveMetadata {
+			name: String::from("Before the boss"),
+			character_name: String::from("Robby"),
+			character_type: String::from("knight"),
+			difficulty: Difficulty::from(String::from("normal")),
+			custom: false,
+			kills: 3,
+			playtime_secs: 61,
+			saved_at: 0,
+			run_id,
+			finished: false,
+			autosave_number: 0,
+		}
+	}
+
+	#[test]
+	fn slot_round_trips_in_every_format() {
+		with_memory_storage(|| {
+			let game = (vec![String::from("Battered Shield")], 2.5, -3);
+			for format in Format::ALL.iter() {
+				set_save_format(*format);
+				SaveSlot::write(1, &metadata(7), &game).unwrap();
+
+				let slot = &SaveSlot::all()[1];
+				assert_eq!(slot.metadata.as_ref().map(|metadata| metadata.run_id), Some(7), "{:?}", format);
+				assert_eq!(SaveSlot::read::<(Vec<String>, f64, i32)>(1).unwrap(), (game.clone(), true), "{:?}", format);
+			}
+			set_save_format(Format::Yaml);
+
+			// Saving in another format replaces the old file rather than leaving both
+			assert_eq!(storage::user_data().list("saves"), vec![String::from("saves/slot_2.msgpack")]);
+			SaveSlot::delete(1).unwrap();
+			assert!(!SaveSlot::any_used());
+		});
+	}
+
+	#[test]
+	fn autosaves_in_the_same_second_keep_rotating() {
+		with_memory_storage(|| {
+			for turn in 1..=3 {
+				Autosave::write(&metadata(7), &turn).unwrap();
+			}
+
+			// All three were written within a second, the newest must still win and the one before it be kept
+			let saves = Autosave::saves();
+			assert_eq!(saves.len(), 2);
+			assert_eq!(saves[0].1.autosave_number, 3);
+			assert_eq!(saves[1].1.autosave_number, 2);
+			assert_eq!(Autosave::read::<i32>().unwrap(), (3, true));
+		});
+	}
+
+	#[derive(Deserialize)]
+	struct Run {
+		run_id: u64,
+	}
+
+	#[test]
+	fn imported_runs_are_finished_when_they_end() {
+		with_memory_storage(|| {
+			let legacy = "player:\n  name: Robby\n  character_type: knight\ndifficulty_settings:\n  difficulty: normal\nenemies_killed: 3\n";
+			storage::user_data().write("current_game.yaml", legacy.as_bytes()).unwrap();
+			SaveSlot::import_legacy();
+			assert!(SaveSlot::all()[0].is_resumable());
+
+			// The run and the slot's metadata have to agree on the id, however often the save is read
+			let (run, _) = SaveSlot::read::<Run>(0).unwrap();
+			assert_ne!(run.run_id, 0);
+			assert_eq!(SaveSlot::read::<Run>(0).unwrap().0.run_id, run.run_id);
+			assert_eq!(SaveSlot::all()[0].metadata.as_ref().map(|metadata| metadata.run_id), Some(run.run_id));
+
+			SaveSlot::finish_run(run.run_id);
+			assert!(!SaveSlot::any_resumable());
+		});
+	}
+}
@@ -0,0 +1,107 @@
+use lazy_static::lazy_static;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use super::{CharacterStats, EventOutcome, ValidationIssue, assets::layered_files_with_extension};
+
+lazy_static! {
+	static ref SHOP_ITEMS: LoadedShopItems = load_shop_items();
+}
+
+/* How many items a merchant has for sale */
+pub const SHOP_STOCK: usize = 3;
+
+struct LoadedShopItems {
+	items: Vec<ShopItem>,
+	issues: Vec<ValidationIssue>,
+}
+
+/*
+	Something a merchant on a Shop node of the map sells. Items are paid for
+	with score, so every purchase lowers the run's place on the leaderboard.
+	They live in the `.yaml` files in `data/shop`.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShopItem {
+	pub id: String,
+	pub name: String,
+	pub description: String,
+	pub price: u32,
+	pub outcomes: Vec<EventOutcome>,
+}
+
+fn load_shop_items() -> LoadedShopItems {
+	if !super::ASSETS_FOLDER.join("data/shop").exists() {
+		init_shop_items();
+	}
+
+	let mut items: Vec<ShopItem> = Vec::new();
+	let mut issues = Vec::new();
+	for path in layered_files_with_extension("data/shop", "yaml") {
+		println!("Loading shop items from {}", path.display());
+		let loaded: Result<Vec<ShopItem>, String> = std::fs::File::open(&path)
+			.map_err(|error| error.to_string())
+			.and_then(|file| serde_yaml::from_reader(file).map_err(|error| error.to_string()));
+		let loaded = match loaded {
+			Ok(loaded) => loaded,
+			Err(error) => {
+				issues.push(ValidationIssue::fatal(&path, None, error));
+				continue;
+			}
+		};
+		for item in loaded {
+			issues.extend(item.validate(&path));
+			// A later file can override an item by reusing its id
+			items.retain(|existing| existing.id != item.id);
+			items.push(item);
+		}
+	}
+	LoadedShopItems { items, issues }
+}
+
+fn init_shop_items() {
+	fn item(id: &str, name: &str, description: &str, price: u32, outcomes: Vec<EventOutcome>) -> ShopItem {
+		ShopItem { id: id.to_string(), name: name.to_string(), description: description.to_string(), price, outcomes }
+	}
+
+	let items = vec![
+		item("potion", "Healing Potion", "Recover half of your health.", 2, vec![EventOutcome::Health { amount: 0.5 }]),
+		item("whetstone", "Whetstone", "A sharper blade, +1 attack for the rest of the run.", 3, vec![EventOutcome::Stats { stats: CharacterStats::new(0, 1, 0, 0) }]),
+		item("buckler", "Buckler", "A small round shield, +1 defense for the rest of the run.", 3, vec![EventOutcome::Item { name: String::from("Buckler"), stats: CharacterStats::new(0, 0, 1, 0) }]),
+		item("rations", "Hearty Rations", "+1 vitality and a good meal.", 4, vec![EventOutcome::Stats { stats: CharacterStats::new(1, 0, 0, 0) }, EventOutcome::Health { amount: 0.15 }]),
+		item("boots", "Light Boots", "+1 stamina for the rest of the run.", 3, vec![EventOutcome::Item { name: String::from("Light Boots"), stats: CharacterStats::new(0, 0, 0, 1) }]),
+	];
+
+	if let Err(error) = super::storage::write_document(&super::storage::assets(), "data/shop/merchant.yaml", &items) {
+		eprintln!("{}", error);
+	}
+}
+
+impl ShopItem {
+	pub fn validation_issues() -> &'static [ValidationIssue] {
+		&SHOP_ITEMS.issues
+	}
+
+	/* Up to `SHOP_STOCK` different items for one visit to a merchant */
+	pub fn stock<R: Rng + ?Sized>(rng: &mut R) -> Vec<&'static ShopItem> {
+		SHOP_ITEMS.items.choose_multiple(rng, SHOP_STOCK).collect()
+	}
+
+	fn validate(&self, file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+		if self.price == 0 {
+			issues.push(ValidationIssue::warning(file, Some("price"), format!("{} is free", self.id)));
+		}
+		for outcome in &self.outcomes {
+			match outcome {
+				EventOutcome::Health { amount } if !(-1.0..=1.0).contains(amount) => {
+					issues.push(ValidationIssue::fatal(file, Some("outcomes"), format!("{} must change health by between -1 and 1 of max health, got {}", self.id, amount)));
+				}
+				EventOutcome::Battle => {
+					issues.push(ValidationIssue::fatal(file, Some("outcomes"), format!("{} can't start a battle, merchants only sell things", self.id)));
+				}
+				_ => {}
+			}
+		}
+		issues
+	}
+}
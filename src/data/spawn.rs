@@ -0,0 +1,170 @@
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+use super::{CharacterType, ValidationIssue};
+
+/*
+	One row of a difficulty's `spawn_table`. A type can only show up once the
+	player has `min_kills` kills, and enemies of that type take their role
+	("the Veteran Soldier") from `names` when it isn't empty.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnEntry {
+	pub character_type: String,
+	#[serde(default = "one")]
+	pub weight: f64,
+	#[serde(default)]
+	pub min_kills: u32,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub names: Vec<String>,
+}
+
+/* The enemy chosen from a spawn table */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawn {
+	pub character_type: &'static CharacterType,
+	pub role: Option<String>,
+}
+
+fn one() -> f64 {
+	1.0
+}
+
+impl SpawnEntry {
+	pub fn new(character_type: &str, weight: f64, min_kills: u32, names: &[&str]) -> SpawnEntry {
+		SpawnEntry {
+			character_type: character_type.to_string(),
+			weight,
+			min_kills,
+			names: names.iter().map(|name| name.to_string()).collect(),
+		}
+	}
+
+	/* The chance of each type spawning after `kills` kills; entries that can't spawn yet are left out */
+	pub fn chances(table: &[SpawnEntry], kills: u32) -> Vec<(&str, f64)> {
+		let eligible: Vec<&SpawnEntry> = table.iter().filter(|entry| entry.min_kills <= kills && entry.weight > 0.0).collect();
+		let total: f64 = eligible.iter().map(|entry| entry.weight).sum();
+		eligible
+			.into_iter()
+			.map(|entry| (entry.character_type.as_str(), entry.weight / total))
+			.collect()
+	}
+
+	/* Picks an entry by weight among those allowed after `kills` kills, or None if there are none */
+	pub fn pick<'a, R: Rng + ?Sized>(table: &'a [SpawnEntry], kills: u32, rng: &mut R) -> Option<&'a SpawnEntry> {
+		let eligible: Vec<&SpawnEntry> = table.iter().filter(|entry| entry.min_kills <= kills && entry.weight > 0.0).collect();
+		let distribution = WeightedIndex::new(eligible.iter().map(|entry| entry.weight)).ok()?;
+		Some(eligible[distribution.sample(rng)])
+	}
+
+	/*
+		Chooses the next enemy for a run. Without a spawn table every character
+		type can spawn according to its manifest weight.
+	*/
+	pub fn spawn<R: Rng + ?Sized>(table: &[SpawnEntry], kills: u32, rng: &mut R) -> Spawn {
+		let entry = SpawnEntry::pick(table, kills, rng);
+		let character_type = entry
+			.and_then(|entry| CharacterType::all().iter().find(|character_type| character_type.id == entry.character_type))
+			.unwrap_or_else(|| CharacterType::random_enemy(rng));
+		let role = match entry {
+			Some(entry) if !entry.names.is_empty() => Some(entry.names[rng.gen_range(0..entry.names.len())].clone()),
+			_ => None,
+		};
+		Spawn { character_type, role }
+	}
+
+	/* Drops rows for unknown types and reports anything that would stop enemies from spawning */
+	pub fn validate(table: &mut Vec<SpawnEntry>, file: &std::path::Path) -> Vec<ValidationIssue> {
+		let mut issues = Vec::new();
+		if table.is_empty() {
+			return issues;
+		}
+
+		table.retain(|entry| {
+			let known = CharacterType::all().iter().any(|character_type| character_type.id == entry.character_type);
+			if !known {
+				issues.push(ValidationIssue::warning(file, Some("spawn_table"), format!("unknown character type {}, ignoring it", entry.character_type)));
+			}
+			known
+		});
+
+		for entry in table.iter() {
+			if !(entry.weight >= 0.0) {
+				issues.push(ValidationIssue::fatal(file, Some("spawn_table"), format!("the weight for {} must not be negative, got {}", entry.character_type, entry.weight)));
+			}
+		}
+
+		if !table.is_empty() && SpawnEntry::chances(table, 0).is_empty() {
+			issues.push(ValidationIssue::fatal(file, Some("spawn_table"), String::from("no character type can spawn before the first kill")));
+		}
+
+		issues
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{rngs::StdRng, SeedableRng};
+
+	fn table() -> Vec<SpawnEntry> {
+		vec![
+			SpawnEntry::new("soldier", 3.0, 0, &[]),
+			SpawnEntry::new("ranger", 1.0, 0, &[]),
+			SpawnEntry::new("knight", 4.0, 5, &[]),
+			SpawnEntry::new("ghost", 0.0, 0, &[]),
+		]
+	}
+
+	fn chance_of(chances: &[(&str, f64)], character_type: &str) -> f64 {
+		chances.iter().find(|(id, _)| *id == character_type).map_or(0.0, |(_, chance)| *chance)
+	}
+
+	#[test]
+	fn chances_add_up_to_one_and_respect_kill_thresholds() {
+		let table = table();
+		for kills in [0, 4, 5, 100].iter() {
+			let total: f64 = SpawnEntry::chances(&table, *kills).iter().map(|(_, chance)| chance).sum();
+			assert!((total - 1.0).abs() < 1e-9, "chances after {} kills sum to {}", kills, total);
+		}
+
+		let early = SpawnEntry::chances(&table, 4);
+		assert_eq!(chance_of(&early, "knight"), 0.0);
+		assert!((chance_of(&early, "soldier") - 0.75).abs() < 1e-9);
+
+		let late = SpawnEntry::chances(&table, 5);
+		assert!((chance_of(&late, "knight") - 0.5).abs() < 1e-9);
+		// A weight of 0 never spawns
+		assert!(late.iter().all(|(id, _)| *id != "ghost"));
+	}
+
+	#[test]
+	fn pick_handles_empty_and_single_entry_tables() {
+		let mut rng = StdRng::seed_from_u64(1);
+		assert_eq!(SpawnEntry::pick(&[], 10, &mut rng), None);
+
+		let locked = vec![SpawnEntry::new("knight", 1.0, 5, &[])];
+		assert_eq!(SpawnEntry::pick(&locked, 4, &mut rng), None);
+
+		let single = vec![SpawnEntry::new("soldier", 2.0, 0, &[])];
+		for _ in 0..20 {
+			assert_eq!(SpawnEntry::pick(&single, 0, &mut rng), Some(&single[0]));
+		}
+	}
+
+	#[test]
+	fn picks_follow_the_chances() {
+		const PICKS: usize = 20_000;
+		let table = table();
+		let mut rng = StdRng::seed_from_u64(33);
+		let mut counts = std::collections::HashMap::new();
+		for _ in 0..PICKS {
+			let entry = SpawnEntry::pick(&table, 10, &mut rng).unwrap();
+			*counts.entry(entry.character_type.as_str()).or_insert(0) += 1;
+		}
+
+		for (character_type, chance) in SpawnEntry::chances(&table, 10) {
+			let observed = *counts.get(character_type).unwrap_or(&0) as f64 / PICKS as f64;
+			assert!((observed - chance).abs() < 0.02, "{} was picked {:.3} of the time, expected {:.3}", character_type, observed, chance);
+		}
+	}
+}
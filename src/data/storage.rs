@@ -0,0 +1,353 @@
+/*
+	Named documents the game reads and writes, e.g. "leaderboard.yaml" or
+	"saves/slot_1.yaml", behind a backend so the code using them doesn't care
+	whether they live on disk or only in memory.
+*/
+
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Serialize};
+use super::Format;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+lazy_static! {
+	static ref USER_DATA: RwLock<Arc<dyn Storage>> = RwLock::new(Arc::new(FileStorage::new(super::USER_DATA_FOLDER.to_path_buf())));
+	static ref USER_CONFIG: RwLock<Arc<dyn Storage>> = RwLock::new(Arc::new(FileStorage::new(super::USER_CONFIG_FOLDER.to_path_buf())));
+}
+
+pub trait Storage: Send + Sync {
+	/* The document called `name`, or `None` if there isn't one */
+	fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String>;
+
+	/* Replaces the document, keeping the previous version as `<name>.bak` */
+	fn write(&self, name: &str, contents: &[u8]) -> Result<(), String>;
+
+	/* The documents directly inside `folder` ("" for the top level), sorted and without backups */
+	fn list(&self, folder: &str) -> Vec<String>;
+
+	/* Removes the document along with its backup, a document that doesn't exist is not an error */
+	fn delete(&self, name: &str) -> Result<(), String>;
+
+	fn exists(&self, name: &str) -> bool {
+		matches!(self.read(name), Ok(Some(_)))
+	}
+
+	/* Where `name` is kept, for messages shown to the player */
+	fn location(&self, name: &str) -> String {
+		name.to_string()
+	}
+}
+
+/* Runs, the leaderboard and other records */
+pub fn user_data() -> Arc<dyn Storage> {
+	USER_DATA.read().unwrap().clone()
+}
+
+/* Presets made in the game, laid out like `assets` */
+pub fn user_config() -> Arc<dyn Storage> {
+	USER_CONFIG.read().unwrap().clone()
+}
+
+/* Swaps the backend behind `user_data`, e.g. for a MemoryStorage */
+pub fn set_user_data(storage: Arc<dyn Storage>) {
+	*USER_DATA.write().unwrap() = storage;
+}
+
+pub fn set_user_config(storage: Arc<dyn Storage>) {
+	*USER_CONFIG.write().unwrap() = storage;
+}
+
+/*
+	Keeps the game playable when a user folder can't be written to, e.g. on a
+	read-only home. Nothing is kept once the game closes.
+*/
+pub fn use_memory_if_unwritable() {
+	if !is_writable(&super::USER_DATA_FOLDER) {
+		eprintln!("Can't write to {}, nothing will be saved this session", super::USER_DATA_FOLDER.display());
+		set_user_data(Arc::new(MemoryStorage::new()));
+	}
+	if !is_writable(&super::USER_CONFIG_FOLDER) {
+		eprintln!("Can't write to {}, presets won't be saved this session", super::USER_CONFIG_FOLDER.display());
+		set_user_config(Arc::new(MemoryStorage::new()));
+	}
+}
+
+fn is_writable(folder: &Path) -> bool {
+	let probe = folder.join(".write_test");
+	let writable = std::fs::create_dir_all(folder).and_then(|_| std::fs::write(&probe, "")).is_ok();
+	std::fs::remove_file(&probe).ok();
+	writable
+}
+
+/* The shipped assets, only written to when a default data file is missing */
+pub fn assets() -> FileStorage {
+	FileStorage::new(super::ASSETS_FOLDER.to_path_buf())
+}
+
+fn backup_name(name: &str) -> String {
+	format!("{}.bak", name)
+}
+
+fn is_backup(name: &str) -> bool {
+	name.ends_with(".bak") || name.ends_with(".tmp")
+}
+
+/*
+	Reads `name` with `parse`, falling back to the backup if the document can't
+	be read or parsed. If both are broken, the error is the one from the
+	document itself.
+*/
+pub fn read_with<T, F>(storage: &dyn Storage, name: &str, parse: F) -> Result<Option<T>, String>
+where
+	F: Fn(&[u8]) -> Result<T, String>,
+{
+	let backup = backup_name(name);
+	let error = match storage.read(name) {
+		Ok(Some(bytes)) => match parse(&bytes) {
+			Ok(value) => return Ok(Some(value)),
+			Err(error) => error,
+		},
+		Ok(None) if !storage.exists(&backup) => return Ok(None),
+		Ok(None) => format!("{} is missing", storage.location(name)),
+		Err(error) => error,
+	};
+
+	match storage.read(&backup).and_then(|bytes| bytes.ok_or_else(String::new)).and_then(|bytes| parse(&bytes)) {
+		Ok(value) => {
+			eprintln!("{}, using the backup at {} instead", error, storage.location(&backup));
+			Ok(Some(value))
+		}
+		Err(_) => Err(error),
+	}
+}
+
+/* Reads `name` in the format its extension names */
+pub fn read_document<T: DeserializeOwned>(storage: &dyn Storage, name: &str) -> Result<Option<T>, String> {
+	let format = Format::of(name)?;
+	read_with(storage, name, |bytes| format.decode(bytes).map_err(|error| format!("{} is corrupt: {}", storage.location(name), error)))
+}
+
+pub fn write_document<T: Serialize + ?Sized>(storage: &dyn Storage, name: &str, value: &T) -> Result<(), String> {
+	// Serialize up front so a value that can't be written never touches the document
+	let bytes = Format::of(name)?.encode(value).map_err(|error| format!("Failed to write {}: {}", storage.location(name), error))?;
+	storage.write(name, &bytes)
+}
+
+/*
+	Documents as files under `root`. A write goes to `<file>.tmp` first, which
+	is synced to disk and then renamed over the real file, so the file is always
+	either the old or the new version, never half of one.
+*/
+pub struct FileStorage {
+	root: PathBuf,
+}
+
+impl FileStorage {
+	pub fn new(root: PathBuf) -> FileStorage {
+		FileStorage { root }
+	}
+
+	pub fn path(&self, name: &str) -> PathBuf {
+		self.root.join(name)
+	}
+
+	fn write_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+		let parent = path.parent().unwrap();
+		std::fs::create_dir_all(parent)?;
+
+		let temp = PathBuf::from(format!("{}.tmp", path.display()));
+		{
+			let mut file = std::fs::File::create(&temp)?;
+			file.write_all(bytes)?;
+			file.sync_all()?;
+		}
+
+		if path.exists() {
+			std::fs::copy(path, format!("{}.bak", path.display()))?;
+		}
+		std::fs::rename(&temp, path)?;
+
+		// Make the rename itself durable, directories can't be opened for this on every platform
+		if let Ok(directory) = std::fs::File::open(parent) {
+			directory.sync_all().ok();
+		}
+		Ok(())
+	}
+}
+
+impl Storage for FileStorage {
+	fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+		match std::fs::read(self.path(name)) {
+			Ok(bytes) => Ok(Some(bytes)),
+			Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			Err(error) => Err(format!("Failed to open {}: {}", self.location(name), error)),
+		}
+	}
+
+	fn write(&self, name: &str, contents: &[u8]) -> Result<(), String> {
+		FileStorage::write_file(&self.path(name), contents)
+			.map_err(|error| format!("Failed to write {}: {}", self.location(name), error))
+	}
+
+	fn list(&self, folder: &str) -> Vec<String> {
+		super::assets::files(&self.root.join(folder))
+			.iter()
+			.map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+			.filter(|file| !is_backup(file))
+			.map(|file| match folder {
+				"" => file,
+				folder => format!("{}/{}", folder, file),
+			})
+			.collect()
+	}
+
+	fn delete(&self, name: &str) -> Result<(), String> {
+		// Try every file even if one fails, so as little as possible is left behind
+		let mut result = Ok(());
+		for file in [name.to_string(), backup_name(name), format!("{}.tmp", name)].iter() {
+			match std::fs::remove_file(self.path(file)) {
+				Err(error) if error.kind() != std::io::ErrorKind::NotFound && result.is_ok() => {
+					result = Err(format!("Failed to delete {}: {}", self.location(file), error));
+				}
+				_ => {}
+			}
+		}
+		result
+	}
+
+	fn location(&self, name: &str) -> String {
+		self.path(name).display().to_string()
+	}
+}
+
+/* Documents that only last as long as the storage itself, nothing touches the disk */
+#[derive(Default)]
+pub struct MemoryStorage {
+	documents: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+	pub fn new() -> MemoryStorage {
+		MemoryStorage::default()
+	}
+}
+
+impl Storage for MemoryStorage {
+	fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+		Ok(self.documents.lock().unwrap().get(name).cloned())
+	}
+
+	fn write(&self, name: &str, contents: &[u8]) -> Result<(), String> {
+		let mut documents = self.documents.lock().unwrap();
+		if let Some(previous) = documents.insert(name.to_string(), contents.to_vec()) {
+			documents.insert(backup_name(name), previous);
+		}
+		Ok(())
+	}
+
+	fn list(&self, folder: &str) -> Vec<String> {
+		let prefix = match folder {
+			"" => String::new(),
+			folder => format!("{}/", folder),
+		};
+		self.documents
+			.lock()
+			.unwrap()
+			.keys()
+			.filter(|name| name.starts_with(&prefix) && !name[prefix.len()..].contains('/') && !is_backup(name))
+			.cloned()
+			.collect()
+	}
+
+	fn delete(&self, name: &str) -> Result<(), String> {
+		let mut documents = self.documents.lock().unwrap();
+		documents.remove(name);
+		documents.remove(&backup_name(name));
+		Ok(())
+	}
+}
+
+/*
+	Runs `test` with `user_data` swapped for an empty MemoryStorage. Tests share
+	the global storage, so they take turns.
+*/
+#[cfg(test)]
+pub fn with_memory_storage<R>(test: impl FnOnce() -> R) -> R {
+	lazy_static! {
+		static ref TURN: Mutex<()> = Mutex::new(());
+	}
+	let _turn = TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	set_user_data(Arc::new(MemoryStorage::new()));
+	test()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::data::{Difficulty, Leaderboard, LeaderboardEntry};
+
+	#[test]
+	fn read_with_falls_back_to_the_backup() {
+		let storage = MemoryStorage::new();
+		write_document(&storage, "numbers.yaml", &vec![1, 2, 3]).unwrap();
+		storage.write("numbers.yaml", b"[1, 2").unwrap();
+
+		let numbers: Option<Vec<u32>> = read_document(&storage, "numbers.yaml").unwrap();
+		assert_eq!(numbers, Some(vec![1, 2, 3]));
+	}
+
+	#[test]
+	fn read_with_reports_the_document_when_both_are_broken() {
+		let storage = MemoryStorage::new();
+		storage.write("numbers.yaml", b"[1").unwrap();
+		storage.write("numbers.yaml", b"[2").unwrap();
+
+		let error = read_document::<Vec<u32>>(&storage, "numbers.yaml").unwrap_err();
+		assert!(error.starts_with("numbers.yaml is corrupt"), "{}", error);
+	}
+
+	#[test]
+	fn missing_documents_read_as_none() {
+		let storage = MemoryStorage::new();
+		assert_eq!(read_document::<Vec<u32>>(&storage, "numbers.yaml").unwrap(), None);
+		assert!(storage.delete("numbers.yaml").is_ok());
+	}
+
+	#[test]
+	fn list_skips_backups_and_subfolders() {
+		let storage = MemoryStorage::new();
+		storage.write("saves/slot_1.yaml", b"a").unwrap();
+		storage.write("saves/slot_1.yaml", b"b").unwrap();
+		storage.write("saves/old/slot_2.yaml", b"c").unwrap();
+		storage.write("leaderboard.yaml", b"d").unwrap();
+
+		assert_eq!(storage.list("saves"), vec![String::from("saves/slot_1.yaml")]);
+		assert_eq!(storage.list(""), vec![String::from("leaderboard.yaml")]);
+	}
+
+	#[test]
+	fn leaderboard_round_trips_in_every_format() {
+		// Signing reads the key from `user_data`, which shouldn't be the real folder
+		with_memory_storage(|| {
+			let storage = MemoryStorage::new();
+			let entry = LeaderboardEntry::new(String::from("Robby"), 12, Difficulty::from(String::from("hard")), false)
+				.with_run(String::from("knight"), 9)
+				.with_replay(42, String::from("abc"))
+				.signed();
+			let leaderboard = Leaderboard { capacity: 10, entries: vec![entry], is_empty: false };
+
+			for format in Format::ALL.iter() {
+				let name = format!("leaderboard.{}", format.extension());
+				write_document(&storage, &name, &leaderboard).unwrap();
+				let read: Leaderboard = read_document(&storage, &name).unwrap().unwrap();
+				assert_eq!(read.capacity, 10);
+				assert_eq!(read.entries.len(), 1);
+				assert_eq!(read.entries[0].name, "Robby");
+				assert_eq!(read.entries[0].kills, 9);
+				assert!(read.entries[0].is_verified(), "{:?}", format);
+			}
+		});
+	}
+}
@@ -0,0 +1,119 @@
+/*
+	Where the files the player creates are kept, apart from the shipped assets.
+
+	Runs, the leaderboard and other records go in the data folder, presets made
+	in the game go in the config folder. Both follow the platform's conventions
+	(`$XDG_DATA_HOME` and `$XDG_CONFIG_HOME` on Linux). A portable install, started
+	with `--portable` or with a `portable` file next to `assets`, keeps everything
+	inside `assets` like older versions did.
+*/
+
+use lazy_static::lazy_static;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const APP_FOLDER: &str = "pp4_remake";
+/* Left in the data folder once the legacy files were moved */
+const MIGRATED_MARKER: &str = ".migrated";
+
+static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+	pub static ref USER_DATA_FOLDER: PathBuf = user_folder(data_home(), super::ASSETS_FOLDER.join("data/runtime"));
+	/* Laid out like `assets`, so it is read as one more layer on top of it */
+	pub static ref USER_CONFIG_FOLDER: PathBuf = user_folder(config_home(), super::ASSETS_FOLDER.to_path_buf());
+}
+
+/* Has to be called before either folder is first used */
+pub fn set_portable(portable: bool) {
+	PORTABLE.store(portable, Ordering::Relaxed);
+}
+
+pub fn is_portable() -> bool {
+	PORTABLE.load(Ordering::Relaxed) || super::ASSETS_FOLDER.parent().unwrap().join("portable").exists()
+}
+
+fn user_folder(home: Option<PathBuf>, portable: PathBuf) -> PathBuf {
+	match (is_portable(), home) {
+		(false, Some(home)) => home.join(APP_FOLDER),
+		_ => portable,
+	}
+}
+
+fn env_path(name: &str) -> Option<PathBuf> {
+	std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(windows)]
+fn data_home() -> Option<PathBuf> {
+	env_path("APPDATA")
+}
+
+#[cfg(windows)]
+fn config_home() -> Option<PathBuf> {
+	env_path("APPDATA")
+}
+
+#[cfg(target_os = "macos")]
+fn data_home() -> Option<PathBuf> {
+	env_path("HOME").map(|home| home.join("Library/Application Support"))
+}
+
+#[cfg(target_os = "macos")]
+fn config_home() -> Option<PathBuf> {
+	env_path("HOME").map(|home| home.join("Library/Preferences"))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn data_home() -> Option<PathBuf> {
+	env_path("XDG_DATA_HOME").or_else(|| env_path("HOME").map(|home| home.join(".local/share")))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn config_home() -> Option<PathBuf> {
+	env_path("XDG_CONFIG_HOME").or_else(|| env_path("HOME").map(|home| home.join(".config")))
+}
+
+/*
+	Moves whatever older versions wrote to `assets/data/runtime` into the data
+	folder. Files already in the data folder are never replaced. This only
+	runs once: on a read-only install the old files stay behind, and moving
+	them again would bring back saves the player has since deleted.
+*/
+pub fn migrate_legacy_runtime() {
+	let legacy = super::ASSETS_FOLDER.join("data/runtime");
+	let marker = USER_DATA_FOLDER.join(MIGRATED_MARKER);
+	if !legacy.exists() || legacy == *USER_DATA_FOLDER || marker.exists() {
+		return;
+	}
+
+	match move_folder(&legacy, &USER_DATA_FOLDER) {
+		Ok(()) => {
+			if let Err(error) = std::fs::write(&marker, "") {
+				eprintln!("Failed to write {}, the old files may be moved again: {}", marker.display(), error);
+			}
+		}
+		Err(error) => eprintln!("Failed to move {}: {}", legacy.display(), error),
+	}
+}
+
+fn move_folder(from: &Path, to: &Path) -> std::io::Result<()> {
+	std::fs::create_dir_all(to)?;
+	for entry in std::fs::read_dir(from)? {
+		let source = entry?.path();
+		let destination = to.join(source.file_name().unwrap());
+		if source.is_dir() {
+			move_folder(&source, &destination)?;
+		} else if !destination.exists() {
+			// A rename can't cross file systems, copying can
+			if std::fs::rename(&source, &destination).is_err() {
+				std::fs::copy(&source, &destination)?;
+				std::fs::remove_file(&source).ok();
+			}
+			println!("Moved {} to {}", source.display(), destination.display());
+		}
+	}
+	// Whatever is left was already in the data folder or can't be removed from a read-only install
+	std::fs::remove_dir(from).ok();
+	Ok(())
+}
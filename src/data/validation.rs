@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+	/* The value was adjusted and loading carried on */
+	Warning,
+	/* The data can't be used as-is; the game refuses to start */
+	Fatal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+	pub file: PathBuf,
+	pub field: Option<String>,
+	pub message: String,
+	pub severity: Severity,
+}
+
+impl ValidationIssue {
+	pub fn warning(file: &std::path::Path, field: Option<&str>, message: String) -> Self {
+		ValidationIssue { file: file.to_path_buf(), field: field.map(String::from), message, severity: Severity::Warning }
+	}
+
+	pub fn fatal(file: &std::path::Path, field: Option<&str>, message: String) -> Self {
+		ValidationIssue { file: file.to_path_buf(), field: field.map(String::from), message, severity: Severity::Fatal }
+	}
+
+	pub fn is_fatal(&self) -> bool {
+		self.severity == Severity::Fatal
+	}
+}
+
+impl std::fmt::Display for ValidationIssue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let severity = match self.severity {
+			Severity::Warning => "warning",
+			Severity::Fatal => "error",
+		};
+		match &self.field {
+			Some(field) => write!(f, "{}: {} ({}): {}", severity, self.file.display(), field, self.message),
+			None => write!(f, "{}: {}: {}", severity, self.file.display(), self.message),
+		}
+	}
+}
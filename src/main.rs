@@ -40,7 +40,7 @@ enum AppTheme {
 }
 
 fn get_cli_options() -> clap::ArgMatches {
-	clap::App::new("MyApp")
+	let app = clap::App::new("MyApp")
 		.arg(
 			Arg::new("fullscreen")
 			.long("fullscreen")
@@ -67,11 +67,108 @@ fn get_cli_options() -> clap::ArgMatches {
 			.possible_values(&["dark", "light", "auto"])
 			.default_value("auto")
 		)
-		.get_matches()
+		.arg(
+			Arg::new("portable")
+			.long("portable")
+			.short('p')
+			.about("Keep saves and settings in the assets folder instead of the user's data folders")
+		)
+		.arg(
+			Arg::new("save-format")
+			.long("save-format")
+			.about("The format new saves are written in, msgpack is the smallest")
+			.setting(ArgSettings::IgnoreCase)
+			.setting(ArgSettings::TakesValue)
+			.possible_values(&["yaml", "json", "ron", "msgpack"])
+			.default_value("yaml")
+		)
+		.arg(
+			Arg::new("seed")
+			.long("seed")
+			.short('s')
+			.about("Seed the run map, so the same layout can be played again")
+			.setting(ArgSettings::TakesValue)
+		)
+		.subcommand(
+			clap::App::new("convert")
+				.about("Convert a save or data file to another format and exit, the formats are picked by extension (.yaml, .json, .ron, .msgpack)")
+				.arg(
+					Arg::new("input")
+					.about("The file to convert")
+					.required(true)
+					.index(1)
+				)
+				.arg(
+					Arg::new("output")
+					.about("Where to write the converted file")
+					.required(true)
+					.index(2)
+				)
+		);
+
+	#[cfg(feature = "sqlite")]
+	let app = app.subcommand(
+		clap::App::new("history")
+			.about("Print the best finished runs and exit")
+			.arg(
+				Arg::new("difficulty")
+				.long("difficulty")
+				.about("Only runs on this difficulty")
+				.setting(ArgSettings::TakesValue)
+			)
+			.arg(
+				Arg::new("character-type")
+				.long("character-type")
+				.about("Only runs with this character type")
+				.setting(ArgSettings::TakesValue)
+			)
+			.arg(
+				Arg::new("days")
+				.long("days")
+				.about("Only runs finished in the last this many days")
+				.setting(ArgSettings::TakesValue)
+			)
+			.arg(
+				Arg::new("count")
+				.long("count")
+				.about("How many runs to print")
+				.setting(ArgSettings::TakesValue)
+				.default_value("20")
+			)
+	);
+
+	app.get_matches()
 }
 
-/* Get width, height and fullscreen mode from the command line arguments. If any of them is not present default to 720p, false */
-fn get_args(args: clap::ArgMatches) -> (u32, u32, AppTheme, bool) {
+#[cfg(feature = "sqlite")]
+fn print_run_history(args: &clap::ArgMatches) {
+	let now = data::now();
+	let filter = data::RunFilter {
+		difficulty: args.value_of("difficulty").map(|difficulty| data::Difficulty::from(difficulty.to_string())),
+		character_type: args.value_of("character-type").map(String::from),
+		finished_after: args.value_of_t::<u64>("days").ok().map(|days| now.saturating_sub(days * 86400)),
+		..data::RunFilter::default()
+	};
+	let count = args.value_of_t("count").unwrap_or(20);
+
+	for (i, entry) in data::RunHistory::top(count, &filter).iter().enumerate() {
+		println!(
+			"{:>3}. {} ({}) - {}, {} kills, {}{} - {}{}",
+			i + 1,
+			entry.name,
+			entry.character_type,
+			entry.score,
+			entry.kills,
+			entry.difficulty.as_str(),
+			if entry.custom { " (custom)" } else { "" },
+			entry.finished_ago_text(),
+			if entry.is_verified() { "" } else { " (unverified)" },
+		);
+	}
+}
+
+/* Get width, height, fullscreen mode and map seed from the command line arguments. If any of them is not present default to 720p, false, random */
+fn get_args(args: clap::ArgMatches) -> (u32, u32, AppTheme, bool, Option<u64>) {
 	let resolution: Vec<u32> = args.values_of_t("resolution").unwrap();
 	
 	let width = std::cmp::max(*resolution.get(0).unwrap(), 600);
@@ -82,8 +179,9 @@ fn get_args(args: clap::ArgMatches) -> (u32, u32, AppTheme, bool) {
 		"light" | _ => AppTheme::Light,
 	};
 	let fullscreen = args.is_present("fullscreen");
+	let seed = args.value_of_t("seed").ok();
 
-	(width, height, app_theme, fullscreen)
+	(width, height, app_theme, fullscreen, seed)
 }
 
 fn load_fonts(fonts: &mut HashMap<&str, conrod_core::text::font::Id>, ui: &mut conrod_core::Ui) {
@@ -102,7 +200,7 @@ fn load_fonts(fonts: &mut HashMap<&str, conrod_core::text::font::Id>, ui: &mut c
 }
 
 fn load_image(display: &glium::Display, path: &str) -> glium::texture::SrgbTexture2d {
-	let image = image::open(&std::path::Path::new(&ASSETS_FOLDER.join("textures").join(path))).unwrap().to_rgba8();
+	let image = image::open(data::resolve_asset(std::path::Path::new("textures").join(path))).unwrap().to_rgba8();
 	let dimensions = image.dimensions();
 	let raw_image = glium::texture::RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
 	glium::texture::SrgbTexture2d::new(display, raw_image).unwrap()
@@ -140,7 +238,50 @@ fn get_display(events_loop: &glium::glutin::EventsLoop, width: u32, height: u32,
 
 
 fn main() {
-	let (width, height, app_theme, fullscreen) = get_args(get_cli_options());
+	let cli_options = get_cli_options();
+
+	if let Some(convert) = cli_options.subcommand_matches("convert") {
+		let (input, output) = (convert.value_of("input").unwrap(), convert.value_of("output").unwrap());
+		match data::convert_file(std::path::Path::new(input), std::path::Path::new(output)) {
+			Ok(()) => println!("Converted {} to {}", input, output),
+			Err(error) => {
+				eprintln!("{}", error);
+				std::process::exit(1);
+			}
+		}
+		return;
+	}
+
+	data::set_portable(cli_options.is_present("portable"));
+	data::set_save_format(data::Format::from_extension(cli_options.value_of("save-format").unwrap()).unwrap());
+	data::migrate_legacy_runtime();
+	data::use_memory_if_unwritable();
+
+	#[cfg(feature = "sqlite")]
+	if let Some(history) = cli_options.subcommand_matches("history") {
+		print_run_history(history);
+		return;
+	}
+
+	let (width, height, app_theme, fullscreen, seed) = get_args(cli_options);
+
+	// Parse the balance formulas up front so a typo fails at startup rather than mid-battle
+	data::BalanceSettings::balance_settings();
+
+	let validation_issues: Vec<_> = data::Mod::validation_issues().iter()
+		.chain(data::DifficultySettings::validation_issues())
+		.chain(data::Event::validation_issues())
+		.chain(data::ShopItem::validation_issues())
+		.chain(data::DialoguePool::validation_issues())
+		.chain(data::BalanceSettings::validation_issues())
+		.collect();
+	for issue in &validation_issues {
+		eprintln!("{}", issue);
+	}
+	if validation_issues.iter().any(|issue| issue.is_fatal()) {
+		eprintln!("The mods, difficulty settings, events, shop items or balance settings contain errors, fix the problems listed above and restart the game.");
+		std::process::exit(1);
+	}
 
 	let mut events_loop = glium::glutin::EventsLoop::new();
 	
@@ -169,6 +310,9 @@ fn main() {
 
 
 	let data_store = data::DataStore::new();
+	if let Some(seed) = seed {
+		data_store.set("run_seed", seed);
+	}
 	let mut is_light_theme = false;
 	let mut theme_manager = ThemeManager::new();
 	match app_theme {
@@ -176,22 +320,26 @@ fn main() {
 			match dark_light::detect() {
 				dark_light::Mode::Light => {
 					is_light_theme = true;
-					theme_manager.set_theme(theme::LIGHT_THEME);
+					theme_manager.set_theme(theme::light_theme());
 				},
 				_ => {}
 			}
 		},
 		AppTheme::Light => {
 			is_light_theme = true;
-			theme_manager.set_theme(theme::LIGHT_THEME);
+			theme_manager.set_theme(theme::light_theme());
 		},
 		AppTheme::Dark => {}
 	};
 
 	data_store.set("is_light_theme", is_light_theme);
 	data_store.set("leaderboard", Leaderboard::make(Some(10)));
-	let has_save_data = ASSETS_FOLDER.join("data/runtime/current_game.yaml").exists();
-	let has_leaderboard = ASSETS_FOLDER.join("data/runtime/leaderboard.yaml").exists();
+	data::SaveSlot::import_legacy();
+	if let Some(autosave) = data::Autosave::recoverable() {
+		data_store.set("recoverable_autosave", autosave);
+	}
+	let has_save_data = data::SaveSlot::any_resumable();
+	let has_leaderboard = data::user_data().exists(Leaderboard::FILE);
 	data_store.set("has_save_data", has_save_data);
 	data_store.set("has_leaderboard", has_leaderboard);
 
@@ -228,9 +376,9 @@ fn main() {
 		if data_store.has("should_toggle_theme") {
 			data_store.remove("should_toggle_theme");
 			if is_light_theme {
-				theme_manager.set_theme(theme::DARK_THEME);
+				theme_manager.set_theme(theme::dark_theme());
 			} else {
-				theme_manager.set_theme(theme::LIGHT_THEME);
+				theme_manager.set_theme(theme::light_theme());
 			}
 			is_light_theme = !is_light_theme;
 			data_store.set("is_light_theme", is_light_theme);
@@ -256,6 +404,14 @@ fn main() {
 		
 		display.0.gl_window().window().set_title(format!("CPP Remake | {} FPS | {:.2}ms", (1.0_f64 / delta.as_secs_f64()) as u64, delta.as_secs_f64() * 1000.0).as_str());
 	}
+
+	// Quitting in the middle of an ironman run counts as fleeing it
+	if let Some(game_data) = data_store.get_t::<scenes::GameData>("game_data") {
+		if game_data.ironman {
+			game_data.end_run();
+			data_store.get_mut_t::<Leaderboard>("leaderboard").unwrap().add_entry(game_data.leaderboard_entry());
+		}
+	}
 }
 
 fn load_images(
@@ -278,10 +434,10 @@ fn load_images(
 	})
 	.collect();
 	
-	for &class in data::ALL_CHARACTER_TYPES {
+	for character_type in data::CharacterType::all() {
 		for &state in data::ALL_CHARACTER_STATES {
-			let id = image_map.insert(load_image(display, format!("characters/{}/head_{}.png", class, state).as_str()));
-			images.insert(format!("{}_{}", class, state), id);
+			let id = image_map.insert(load_image(display, format!("characters/{}/head_{}.png", character_type.id, state).as_str()));
+			images.insert(format!("{}_{}", character_type.id, state), id);
 		}
 	}
 
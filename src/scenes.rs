@@ -2,8 +2,15 @@ extern crate paste;
 
 mod main_menu;
 mod difficulty_selection;
+mod difficulty_editor;
+mod modifier_selection;
 mod character_creation;
 mod game;
+mod interlude;
+mod event;
+mod map;
+mod shop;
+mod save_browser;
 mod game_over;
 #[macro_use] mod macros;
 
@@ -17,8 +24,15 @@ pub use generate_scene;
 
 pub use main_menu::MainMenu;
 pub use difficulty_selection::DifficultySelection;
+pub use difficulty_editor::DifficultyEditor;
+pub use modifier_selection::ModifierSelection;
 pub use character_creation::CharacterCreation;
-pub use game::Game;
+pub use game::{Game, actions::GameData};
+pub use interlude::Interlude;
+pub use event::Event;
+pub use map::Map;
+pub use shop::Shop;
+pub use save_browser::{SaveBrowser, SaveBrowserMode};
 pub use game_over::GameOver;
 
 pub trait Scene {
@@ -42,7 +56,7 @@ pub struct SceneManager<'a> {
 	events_loop_proxy: &'a glium::glutin::EventsLoopProxy,
 }
 
-generate_scene_collection!(MainMenu, DifficultySelection, CharacterCreation, Game, GameOver);
+generate_scene_collection!(MainMenu, DifficultySelection, DifficultyEditor, ModifierSelection, CharacterCreation, Game, Interlude, Event, Map, Shop, SaveBrowser, GameOver);
 
 #[allow(dead_code)]
 impl<'a> SceneManager<'a> {
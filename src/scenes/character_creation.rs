@@ -33,6 +33,7 @@ widget_ids! {
         text_attack,
         text_defense,
         text_stamina,
+        text_derived_stats,
 
         button_randomize_name,
         button_randomize_stats,
@@ -68,8 +69,8 @@ impl Scene for CharacterCreation {
         let mut rng = rand::thread_rng();
 
         if !data_store.has("create_character_settings") {
-            let character_name: usize = rng.gen_range(0..data::CHARACTER_NAME_COUNT);
-            let character_type: usize = rng.gen_range(0..data::CHARACTER_TYPE_COUNT);
+            let character_name: usize = rng.gen_range(0..data::character_names().len());
+            let character_type: usize = rng.gen_range(0..data::CharacterType::all().len());
             data_store.set("create_character_settings", CreateCharacterSettings::new(character_name, character_type));
         }
 
@@ -77,9 +78,9 @@ impl Scene for CharacterCreation {
         let mut create_character_settings: &mut CreateCharacterSettings;
         let base_character_stats: data::CharacterStats;
         {
-            difficulty_settings = *data::DifficultySettings::difficulty_settings().get(data_store.get_t::<data::Difficulty>("difficulty").unwrap().as_str()).unwrap();
+            difficulty_settings = (*data_store.get_t::<data::DifficultySettings>("difficulty_settings").unwrap()).clone();
             create_character_settings = *data_store.get_mut_t::<CreateCharacterSettings>("create_character_settings").unwrap(); 
-            base_character_stats = *data::CharacterStats::base_character_stats().get(data::ALL_CHARACTER_TYPES[create_character_settings.character_type]).unwrap();
+            base_character_stats = data::CharacterType::all()[create_character_settings.character_type].base_stats;
         }
          
         
@@ -124,7 +125,7 @@ impl Scene for CharacterCreation {
             .w_h(ui.win_w / 2.0, ui.win_h-TITLE_HEIGHT)
             .set(ids.right_col, ui);
 
-        widget::Text::new(&(String::from("Name: ") + data::ALL_CHARACTER_NAMES[create_character_settings.name]))
+        widget::Text::new(&(String::from("Name: ") + &data::character_names()[create_character_settings.name]))
             .color(theme.text_primary)
             .font_size(32)
             .font_id(*fonts.get("lato").unwrap())
@@ -151,13 +152,13 @@ impl Scene for CharacterCreation {
             .set(ids.button_randomize_name, ui)
             .was_clicked()
         {
-            let character_name: usize = rng.gen_range(0..data::CHARACTER_NAME_COUNT);
+            let character_name: usize = rng.gen_range(0..data::character_names().len());
             create_character_settings.name = character_name;
             scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
         }
 
-        let character_type = data::ALL_CHARACTER_TYPES[create_character_settings.character_type];
-        let image_id = images.get(&(String::from(character_type) + "_idle")).unwrap();
+        let character_type = &data::CharacterType::all()[create_character_settings.character_type];
+        let image_id = images.get(&(character_type.id.clone() + "_idle")).unwrap();
         let (image_w, image_h) = image_map.get(image_id).unwrap().dimensions();
         let image_ratio = image_w as f64 / image_h as f64;
         let image_size: f64 = {
@@ -180,7 +181,7 @@ impl Scene for CharacterCreation {
             .x_place_on(ids.left_col, Place::Middle)
             .set(ids.image, ui);
 
-        widget::Text::new(&character_type.to_uppercase())
+        widget::Text::new(&character_type.display_name.to_uppercase())
             .color(theme.text_primary)
             .font_size(32)
             .font_id(*fonts.get("lato").unwrap())
@@ -198,7 +199,7 @@ impl Scene for CharacterCreation {
             .was_clicked()
         {
             if create_character_settings.character_type == 0 {
-                create_character_settings.character_type = data::CHARACTER_TYPE_COUNT - 1;
+                create_character_settings.character_type = data::CharacterType::all().len() - 1;
             } else {
                 create_character_settings.character_type = create_character_settings.character_type - 1;
             }
@@ -214,7 +215,7 @@ impl Scene for CharacterCreation {
             .set(ids.button_next_character_type, ui)
             .was_clicked()
         {
-            create_character_settings.character_type = (create_character_settings.character_type + 1) % data::CHARACTER_TYPE_COUNT;
+            create_character_settings.character_type = (create_character_settings.character_type + 1) % data::CharacterType::all().len();
             scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
         }
 
@@ -391,6 +392,26 @@ impl Scene for CharacterCreation {
             scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
         }
 
+        let final_stats = base_character_stats + create_character_settings.assigned_stats;
+        let balance_settings = data::BalanceSettings::balance_settings();
+        let (attack_min, attack_max) = balance_settings.attack_range(&final_stats);
+        let (defense_min, defense_max) = balance_settings.defense_range(&final_stats);
+        let (heal_min, heal_max) = balance_settings.heal_range(&final_stats);
+        // Rolls never go below 1, so the preview shouldn't either
+        widget::Text::new(&format!(
+                "Health: {}   Attack: {}-{}   Defense: {}-{}   Heal: {}-{}",
+                balance_settings.max_health(&final_stats),
+                attack_min.max(1), attack_max.max(1),
+                defense_min.max(1), defense_max.max(1),
+                heal_min.max(1), heal_max.max(1),
+            ))
+            .color(theme.text_primary)
+            .font_size(24)
+            .font_id(*fonts.get("lato").unwrap())
+            .y_place_on(ids.button_randomize_stats, Place::Start(Some(-40.0)))
+            .x_place_on(ids.right_col, Place::Start(Some(24.0)))
+            .set(ids.text_derived_stats, ui);
+
         if base_button.clone()
             .label("Back")
             .w(256.0)
@@ -400,7 +421,7 @@ impl Scene for CharacterCreation {
             .was_clicked()
         {
             data_store.remove("create_character_settings");
-            self.next_scene_index = Some(SceneManager::DIFFICULTY_SELECTION);
+            self.next_scene_index = Some(SceneManager::MODIFIER_SELECTION);
             scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
         }
 
@@ -413,14 +434,23 @@ impl Scene for CharacterCreation {
             .was_clicked()
         {
             let player = Character::new(
-                data::ALL_CHARACTER_NAMES[create_character_settings.name].to_string(), 
-                character_type.to_string(),
-                base_character_stats + create_character_settings.assigned_stats, 
+                data::character_names()[create_character_settings.name].clone(), 
+                character_type.id.clone(),
+                final_stats, 
                 difficulty_settings.clone()
             );
-            let game_data = GameData::new(player, difficulty_settings.clone());
+            let modifiers = data_store.get_t::<Vec<data::RunModifier>>("run_modifiers")
+                .map(|modifiers| (*modifiers).clone())
+                .unwrap_or_default();
+            let seed = data_store.get_t::<u64>("run_seed")
+                .map(|seed| **seed)
+                .unwrap_or_else(rand::random);
+            let ironman = data_store.get_t::<bool>("ironman").map_or(false, |ironman| **ironman);
+            let game_data = GameData::new(player, difficulty_settings.clone(), modifiers, seed, ironman);
             data_store.set("game_data", game_data);
             data_store.remove("create_character_settings");
+            data_store.remove("run_modifiers");
+            data_store.remove("ironman");
             self.next_scene_index = Some(SceneManager::GAME);
         }
 
@@ -0,0 +1,387 @@
+use conrod_core::{
+	position::{Align, Place},
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		title,
+
+		slider_enemy_base_attribute_points,
+		slider_enemy_attack_chance,
+		slider_enemy_heal_chance,
+		slider_enemy_do_nothing_chance,
+		slider_enemy_evade_chance,
+		slider_player_base_attribute_points,
+		slider_player_focus_chance,
+		slider_player_evade_chance,
+		slider_elite_chance,
+
+		text_interludes,
+		interlude_toggles[],
+
+		summary_title,
+		summary,
+
+		text_preset_name,
+		textbox_preset_name,
+		button_save,
+		button_load,
+		text_message,
+
+		back,
+		play,
+	}
+}
+
+generate_scene!(DifficultyEditor -> Ids);
+
+impl Scene for DifficultyEditor {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		if !data_store.has("difficulty_editor_state") {
+			data_store.set("difficulty_editor_state", DifficultyEditorState::new());
+		}
+		let state = *data_store.get_mut_t::<DifficultyEditorState>("difficulty_editor_state").unwrap();
+
+		if self.ids.interlude_toggles.len() != state.interlude_choices.len() {
+			self.ids.interlude_toggles.resize(state.interlude_choices.len(), &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		const SLIDER_HEIGHT: f64 = 40.0;
+		const SLIDER_SPACING: f64 = 8.0;
+		const COLUMN_MARGIN: f64 = 48.0;
+		const TOP_MARGIN: f64 = 128.0;
+		let column_width = ui.win_w / 2.0 - COLUMN_MARGIN * 2.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Text::new("Custom difficulty")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(56)
+			.x_align_to(ids.root, Align::Middle)
+			.y_place_on(ids.root, Place::End(Some(24.0)))
+			.set(ids.title, ui);
+
+		let settings = &mut state.settings;
+		let sliders: [(widget::Id, &str, f64, f64, f64, bool); 9] = [
+			(ids.slider_enemy_base_attribute_points, "Enemy attribute points", settings.enemy_base_attribute_points as f64, 1.0, 30.0, true),
+			(ids.slider_enemy_attack_chance, "Enemy attack weight", settings.enemy_attack_chance, 0.0, 1.0, false),
+			(ids.slider_enemy_heal_chance, "Enemy heal weight", settings.enemy_heal_chance, 0.0, 1.0, false),
+			(ids.slider_enemy_do_nothing_chance, "Enemy hesitate weight", settings.enemy_do_nothing_chance, 0.0, 1.0, false),
+			(ids.slider_enemy_evade_chance, "Enemy evade chance", settings.enemy_evade_chance, 0.0, 1.0, false),
+			(ids.slider_player_base_attribute_points, "Player attribute points", settings.player_base_attribute_points as f64, 1.0, 30.0, true),
+			(ids.slider_player_focus_chance, "Player focus chance", settings.player_focus_chance, 0.0, 1.0, false),
+			(ids.slider_player_evade_chance, "Player evade chance", settings.player_evade_chance, 0.0, 1.0, false),
+			(ids.slider_elite_chance, "Elite chance", settings.elite_chance, 0.0, 1.0, false),
+		];
+
+		let mut changed_values = [None; 9];
+		for (i, &(slider_id, label, value, min, max, is_integer)) in sliders.iter().enumerate() {
+			let label = match is_integer {
+				true => format!("{}: {}", label, value as i32),
+				false => format!("{}: {:.2}", label, value),
+			};
+			changed_values[i] = widget::Slider::new(value, min, max)
+				.label(&label)
+				.label_font_id(*fonts.get("lato").unwrap())
+				.label_font_size(20)
+				.label_color(theme.text_light)
+				.color(theme.accend_color_secondary)
+				.border(0.0)
+				.w_h(column_width, SLIDER_HEIGHT)
+				.x_place_on(ids.root, Place::Start(Some(COLUMN_MARGIN)))
+				.y_place_on(ids.root, Place::End(Some(TOP_MARGIN + i as f64 * (SLIDER_HEIGHT + SLIDER_SPACING))))
+				.set(slider_id, ui)
+				.map(|new_value| if is_integer { new_value.round() } else { new_value });
+		}
+
+		let [
+			enemy_base_attribute_points, enemy_attack_chance, enemy_heal_chance, enemy_do_nothing_chance,
+			enemy_evade_chance, player_base_attribute_points, player_focus_chance, player_evade_chance, elite_chance,
+		] = changed_values;
+		if let Some(value) = enemy_base_attribute_points { settings.enemy_base_attribute_points = value as i32; }
+		if let Some(value) = enemy_attack_chance { settings.enemy_attack_chance = value; }
+		if let Some(value) = enemy_heal_chance { settings.enemy_heal_chance = value; }
+		if let Some(value) = enemy_do_nothing_chance { settings.enemy_do_nothing_chance = value; }
+		if let Some(value) = enemy_evade_chance { settings.enemy_evade_chance = value; }
+		if let Some(value) = player_base_attribute_points { settings.player_base_attribute_points = value as i32; }
+		if let Some(value) = player_focus_chance { settings.player_focus_chance = value; }
+		if let Some(value) = player_evade_chance { settings.player_evade_chance = value; }
+		if let Some(value) = elite_chance { settings.elite_chance = value; }
+
+		widget::Text::new("Between battles:")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.x_place_on(ids.root, Place::Start(Some(COLUMN_MARGIN)))
+			.y_place_on(ids.root, Place::End(Some(TOP_MARGIN + sliders.len() as f64 * (SLIDER_HEIGHT + SLIDER_SPACING) + 8.0)))
+			.set(ids.text_interludes, ui);
+
+		// Each option the base preset or the game offers can be switched on or off, with none there are no interludes
+		let toggle_width = (column_width - 160.0) / state.interlude_choices.len().max(1) as f64 - 8.0;
+		let mut toggled = None;
+		for (i, option) in state.interlude_choices.iter().enumerate() {
+			let enabled = settings.interlude_options.iter().any(|enabled| enabled.id == option.id);
+			let toggle = widget::Button::new()
+				.label(&option.name)
+				.label_font_id(*fonts.get("lato").unwrap())
+				.label_font_size(18)
+				.w_h(toggle_width, 36.0)
+				.border(0.0);
+			let toggle = match enabled {
+				true => toggle.color(theme.accend_color_secondary).hover_color(theme.accend_color_secondary).press_color(theme.button_press).label_color(theme.text_light),
+				false => toggle.color(theme.button_disabled).hover_color(theme.button_hover).press_color(theme.button_press).label_color(theme.text_secondary),
+			};
+			let toggle = match i {
+				0 => toggle.x_place_on(ids.root, Place::Start(Some(COLUMN_MARGIN + 160.0))).y_align_to(ids.text_interludes, Align::Middle),
+				_ => toggle.right_from(ids.interlude_toggles[i - 1], 8.0),
+			};
+			if toggle.set(ids.interlude_toggles[i], ui).was_clicked() {
+				toggled = Some(option.id.clone());
+			}
+		}
+		if let Some(id) = toggled {
+			match settings.interlude_options.iter().any(|enabled| enabled.id == id) {
+				true => settings.interlude_options.retain(|enabled| enabled.id != id),
+				false => {
+					let choices = &state.interlude_choices;
+					settings.interlude_options = choices.iter()
+						.filter(|choice| choice.id == id || settings.interlude_options.iter().any(|enabled| enabled.id == choice.id))
+						.cloned()
+						.collect();
+				}
+			}
+		}
+
+		let summary = settings.summary();
+		let summary_text = format!(
+			"Average enemy health: {}\nAverage player health: {}\nExpected enemy damage per turn: {:.1}\nExpected enemy healing per turn: {:.1}\nYour expected damage per attack: {:.1}\nAttacks to defeat an average enemy: {}\n\nThe spawn table can't be changed here, enemies appear as in {}. Edit the saved preset's file to change it.",
+			summary.enemy_health,
+			summary.player_health,
+			summary.enemy_damage_per_turn,
+			summary.enemy_healing_per_turn,
+			summary.player_damage_per_attack,
+			match summary.attacks_to_kill_enemy.is_finite() {
+				true => format!("{}", summary.attacks_to_kill_enemy),
+				false => String::from("never"),
+			},
+			state.based_on,
+		);
+
+		widget::Text::new("What this means")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(32)
+			.x_place_on(ids.root, Place::Start(Some(ui.win_w / 2.0 + COLUMN_MARGIN)))
+			.y_place_on(ids.root, Place::End(Some(TOP_MARGIN)))
+			.set(ids.summary_title, ui);
+
+		widget::Text::new(&summary_text)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(22)
+			.line_spacing(8.0)
+			.w(column_width)
+			.align_left_of(ids.summary_title)
+			.down_from(ids.summary_title, 16.0)
+			.set(ids.summary, ui);
+
+		widget::Text::new("Preset name")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(24)
+			.align_left_of(ids.summary_title)
+			.down_from(ids.summary, 32.0)
+			.set(ids.text_preset_name, ui);
+
+		for event in widget::TextBox::new(&state.preset_name)
+			.font_size(24)
+			.w_h(column_width, 40.0)
+			.color(theme.panel_light)
+			.text_color(theme.text_dark)
+			.border(0.0)
+			.align_left_of(ids.summary_title)
+			.down_from(ids.text_preset_name, 8.0)
+			.set(ids.textbox_preset_name, ui)
+		{
+			if let widget::text_box::Event::Update(name) = event {
+				state.preset_name = name;
+			}
+		}
+
+		let base_button = widget::Button::new()
+			.h(48.0)
+			.color(theme.button_normal)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.border(0.0)
+			.label_font_size(24)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		let button_width = (column_width - 8.0) / 2.0;
+		if base_button.clone()
+			.label("Save preset")
+			.w(button_width)
+			.align_left_of(ids.summary_title)
+			.down_from(ids.textbox_preset_name, 8.0)
+			.set(ids.button_save, ui)
+			.was_clicked()
+		{
+			let mut preset = state.settings.clone();
+			state.message = match preset.save_as_preset(&state.preset_name) {
+				Ok(location) => {
+					state.presets = data::DifficultySettings::custom_presets().into_iter().cloned().collect();
+					state.settings = preset;
+					// Presets live with the player's settings so reinstalling or updating the game keeps them
+					format!("Saved to {}, it can be picked from the difficulty list now", location)
+				}
+				Err(error) => error,
+			};
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+
+		let mut load_button = base_button.clone()
+			.label("Load next preset")
+			.w(button_width)
+			.right_from(ids.button_save, 8.0);
+		if state.presets.is_empty() {
+			load_button = load_button
+				.color(theme.button_disabled)
+				.hover_color(theme.button_disabled)
+				.press_color(theme.button_disabled);
+		}
+
+		if load_button
+			.set(ids.button_load, ui)
+			.was_clicked()
+			&& !state.presets.is_empty()
+		{
+			state.preset_index %= state.presets.len();
+			let preset = state.presets[state.preset_index].clone();
+			state.message = format!("Loaded {}", preset.name);
+			state.preset_name = preset.name.clone();
+			state.based_on = preset.name.clone();
+			state.interlude_choices = interlude_choices(&preset);
+			state.settings = preset;
+			state.preset_index += 1;
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+
+		widget::Text::new(&state.message)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.w(column_width)
+			.align_left_of(ids.summary_title)
+			.down_from(ids.button_save, 16.0)
+			.set(ids.text_message, ui);
+
+		if base_button.clone()
+			.label("Back")
+			.w(256.0)
+			.bottom_left_with_margins_on(ids.root, 24.0, COLUMN_MARGIN)
+			.set(ids.back, ui)
+			.was_clicked()
+		{
+			data_store.remove("difficulty_editor_state");
+			self.next_scene_index = Some(SceneManager::DIFFICULTY_SELECTION);
+		}
+
+		if base_button.clone()
+			.label("Play")
+			.w(256.0)
+			.bottom_right_with_margins_on(ids.root, 24.0, COLUMN_MARGIN)
+			.set(ids.play, ui)
+			.was_clicked()
+		{
+			let mut settings = state.settings.clone();
+			let issues = settings.validate(std::path::Path::new(&format!("{}.yaml", settings.difficulty.as_str())));
+			match issues.iter().find(|issue| issue.is_fatal()) {
+				Some(issue) => {
+					state.message = format!("{}: {}", issue.field.as_deref().unwrap_or("settings"), issue.message);
+				}
+				None => {
+					data_store.set("difficulty_settings", settings);
+					data_store.remove("difficulty_editor_state");
+					self.next_scene_index = Some(SceneManager::MODIFIER_SELECTION);
+				}
+			}
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
+
+#[derive(Debug, Clone)]
+struct DifficultyEditorState {
+	settings: data::DifficultySettings,
+	preset_name: String,
+	presets: Vec<data::DifficultySettings>,
+	preset_index: usize,
+	message: String,
+	/* The preset the spawn table comes from, the editor has no controls for it */
+	based_on: String,
+	/* The interlude options that can be switched on, in the order they are offered */
+	interlude_choices: Vec<data::InterludeOption>,
+}
+
+impl DifficultyEditorState {
+	fn new() -> DifficultyEditorState {
+		// Start from the middle of the official tiers, it's the most sensible baseline
+		let mut official = data::DifficultySettings::official();
+		if official.is_empty() {
+			official = data::DifficultySettings::sorted();
+		}
+		let mut settings = official[official.len() / 2].clone();
+		let based_on = settings.name.clone();
+		settings.name = String::from("Custom");
+		settings.description = String::from("Custom preset");
+		settings.custom = true;
+		settings.difficulty = data::Difficulty::from(String::from("custom"));
+
+		DifficultyEditorState {
+			preset_name: String::new(),
+			presets: data::DifficultySettings::custom_presets().into_iter().cloned().collect(),
+			preset_index: 0,
+			message: String::new(),
+			based_on,
+			interlude_choices: interlude_choices(&settings),
+			settings,
+		}
+	}
+}
+
+/* The preset's own interlude options followed by any of the game's defaults it leaves out */
+fn interlude_choices(settings: &data::DifficultySettings) -> Vec<data::InterludeOption> {
+	let mut choices = settings.interlude_options.clone();
+	for option in data::InterludeOption::defaults() {
+		if !choices.iter().any(|choice| choice.id == option.id) {
+			choices.push(option);
+		}
+	}
+	choices
+}
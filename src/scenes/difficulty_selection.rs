@@ -13,9 +13,9 @@ widget_ids! {
 		
 		title,
 		
-		easy,
-		normal,
-		hard,
+		difficulty_buttons[],
+		custom,
+		description,
 		
 		back
 	}
@@ -34,16 +34,21 @@ impl Scene for DifficultySelection {
 		theme: &theme::Theme,
 		data_store: &data::DataStore,
 	) {
+		let difficulties = data::DifficultySettings::official();
+		if self.ids.difficulty_buttons.len() != difficulties.len() {
+			self.ids.difficulty_buttons.resize(difficulties.len(), &mut ui.widget_id_generator());
+		}
 		let ids = &self.ids;
 
 		const BUTTON_HEIGHT: f64 = 48.0;
 		const BUTTON_WIDTH: f64 = 256.0;
 		const BUTTON_SPACING: f64 = 8.0;
-		const TOTAL_BUTTONS_HEIGHT: f64 = BUTTON_HEIGHT * 3.0 + BUTTON_SPACING * 2.0;
 		const TITLE_HEIGHT: f64 = 64.0;
+		let button_count = difficulties.len() as f64 + 1.0 /* Custom */;
+		let total_buttons_height = BUTTON_HEIGHT * button_count + BUTTON_SPACING * (button_count - 1.0).max(0.0);
 		
 		let win_height = ui.win_h;
-		let remaining_height = win_height - TOTAL_BUTTONS_HEIGHT - 2.0 * TITLE_HEIGHT;
+		let remaining_height = win_height - total_buttons_height - 2.0 * TITLE_HEIGHT;
 		let vertical_spacing = remaining_height / 2.0;
 
 		widget::Canvas::new()
@@ -72,34 +77,50 @@ impl Scene for DifficultySelection {
 			.label_font_size(24)
 			.label_color(theme.text_secondary)
 			.label_font_id(*fonts.get("lato").unwrap());
-		
-		if base_button.clone()
-			.label("Easy")
-			.mid_top_with_margin_on(ids.title, BUTTON_HEIGHT)
-			.set(ids.easy, ui)
-			.was_clicked() {
-				data_store.set("difficulty", data::Difficulty::Easy);
-				self.next_scene_index = Some(SceneManager::CHARACTER_CREATION);
-			}
 
-		if base_button.clone()
-			.label("Normal")
-			.mid_bottom_with_margin_on(ids.easy, -(BUTTON_SPACING + BUTTON_HEIGHT))
-			.set(ids.normal, ui)
-			.was_clicked() {
-				data_store.set("difficulty",data::Difficulty::Normal);
-				self.next_scene_index = Some(SceneManager::CHARACTER_CREATION);
+		let mut hovered_description: Option<&str> = None;
+		for (i, settings) in difficulties.iter().enumerate() {
+			let button_id = ids.difficulty_buttons[i];
+			let button = match i {
+				0 => base_button.clone().mid_top_with_margin_on(ids.title, BUTTON_HEIGHT),
+				_ => base_button.clone().mid_bottom_with_margin_on(ids.difficulty_buttons[i - 1], -(BUTTON_SPACING + BUTTON_HEIGHT)),
+			};
+
+			if button
+				.label(&settings.name)
+				.set(button_id, ui)
+				.was_clicked() {
+					data_store.set("difficulty_settings", (*settings).clone());
+					self.next_scene_index = Some(SceneManager::MODIFIER_SELECTION);
+				}
+
+			if ui.widget_input(button_id).mouse().is_some() {
+				hovered_description = Some(&settings.description);
 			}
+		}
 
-		if base_button.clone()
-			.label("Hard")
-			.mid_bottom_with_margin_on(ids.normal, -(BUTTON_SPACING + BUTTON_HEIGHT))
-			.set(ids.hard, ui)
+		let custom_button = match ids.difficulty_buttons.last() {
+			Some(&last_button) => base_button.clone().mid_bottom_with_margin_on(last_button, -(BUTTON_SPACING + BUTTON_HEIGHT)),
+			None => base_button.clone().mid_top_with_margin_on(ids.title, BUTTON_HEIGHT),
+		};
+		if custom_button
+			.label("Custom")
+			.set(ids.custom, ui)
 			.was_clicked() {
-				data_store.set("difficulty",data::Difficulty::Hard);
-				self.next_scene_index = Some(SceneManager::CHARACTER_CREATION);
+				self.next_scene_index = Some(SceneManager::DIFFICULTY_EDITOR);
 			}
 
+		if ui.widget_input(ids.custom).mouse().is_some() {
+			hovered_description = Some("Tune every setting yourself. Custom runs are ranked separately.");
+		}
+
+		widget::Text::new(hovered_description.unwrap_or(""))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.mid_bottom_with_margin_on(ids.custom, -(BUTTON_SPACING * 2.0 + 20.0))
+			.set(ids.description, ui);
+
 		if widget::Button::new()
 			.color(theme.button_normal)
 			.hover_color(theme.button_hover)
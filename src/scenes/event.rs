@@ -0,0 +1,182 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager, game::actions::GameData},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		panel,
+		title,
+		description,
+
+		choice_buttons[],
+
+		result,
+
+		continue_button,
+	}
+}
+
+generate_scene!(Event -> Ids);
+
+impl Scene for Event {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let game_data = *data_store.get_mut_t::<GameData>("game_data").unwrap();
+
+		if !data_store.has("event_state") {
+			match data::Event::pick(game_data.depth(), &mut rand::thread_rng()) {
+				Some(event) => data_store.set("event_state", EventState { event, chosen: None, result: String::new(), battle: false }),
+				// Nothing fits this far into the run, so the road stays quiet
+				None => {
+					self.next_scene_index = Some(SceneManager::MAP);
+					scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+					return;
+				}
+			}
+		}
+		let state = *data_store.get_mut_t::<EventState>("event_state").unwrap();
+		let event = state.event;
+
+		if self.ids.choice_buttons.len() != event.choices.len() {
+			self.ids.choice_buttons.resize(event.choices.len(), &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		const PANEL_WIDTH: f64 = 720.0;
+		const PANEL_HEIGHT: f64 = 560.0;
+		const PANEL_MARGIN: f64 = 32.0;
+		const BUTTON_HEIGHT: f64 = 56.0;
+		const BUTTON_SPACING: f64 = 8.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Canvas::new()
+			.color(theme.panel_dark)
+			.border(0.0)
+			.w_h(PANEL_WIDTH, PANEL_HEIGHT)
+			.middle_of(ids.root)
+			.set(ids.panel, ui);
+
+		widget::Text::new(&event.title)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(40)
+			.mid_top_with_margin_on(ids.panel, PANEL_MARGIN)
+			.set(ids.title, ui);
+
+		widget::Text::new(&event.description)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(22)
+			.w(PANEL_WIDTH - PANEL_MARGIN * 2.0)
+			.wrap_by_word()
+			.center_justify()
+			.line_spacing(6.0)
+			.down_from(ids.title, PANEL_MARGIN)
+			.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+			.set(ids.description, ui);
+
+		let base_button = widget::Button::new()
+			.w_h(PANEL_WIDTH - PANEL_MARGIN * 2.0, BUTTON_HEIGHT)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.border(0.0)
+			.label_font_size(22)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		for (i, choice) in event.choices.iter().enumerate() {
+			let button = match i {
+				0 => base_button.clone().down_from(ids.description, PANEL_MARGIN),
+				_ => base_button.clone().down_from(ids.choice_buttons[i - 1], BUTTON_SPACING),
+			};
+			let button = match (state.chosen, state.chosen == Some(i)) {
+				(_, true) => button.color(theme.accend_color_secondary).label_color(theme.text_light),
+				(Some(_), false) => button.color(theme.button_disabled).hover_color(theme.button_disabled).press_color(theme.button_disabled),
+				(None, false) => button.color(theme.button_normal),
+			};
+
+			if button
+				.label(&choice.text)
+				.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+				.set(ids.choice_buttons[i], ui)
+				.was_clicked()
+				&& state.chosen.is_none()
+			{
+				let (result, battle) = game_data.apply_event_choice(choice);
+				game_data.autosave_if_needed();
+				state.chosen = Some(i);
+				state.result = result;
+				state.battle = battle;
+				scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+			}
+		}
+
+		let result = widget::Text::new(&state.result)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.center_justify()
+			.line_spacing(6.0);
+		match ids.choice_buttons.last() {
+			Some(&last_button) => result.down_from(last_button, PANEL_MARGIN),
+			None => result.down_from(ids.description, PANEL_MARGIN),
+		}
+		.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+		.set(ids.result, ui);
+
+		let mut continue_button = base_button.clone()
+			.w(320.0)
+			.label(match state.battle { true => "Fight", false => "Continue" })
+			.color(theme.button_normal)
+			.mid_bottom_with_margin_on(ids.root, 24.0);
+		if state.chosen.is_none() {
+			continue_button = continue_button
+				.color(theme.button_disabled)
+				.hover_color(theme.button_disabled)
+				.press_color(theme.button_disabled);
+		}
+
+		if continue_button
+			.set(ids.continue_button, ui)
+			.was_clicked()
+			&& state.chosen.is_some()
+		{
+			self.next_scene_index = match state.battle {
+				true => Some(SceneManager::GAME),
+				false => Some(SceneManager::MAP),
+			};
+			data_store.remove("event_state");
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
+
+#[derive(Debug, Clone)]
+struct EventState {
+	event: &'static data::Event,
+	chosen: Option<usize>,
+	result: String,
+	battle: bool,
+}
@@ -1,6 +1,6 @@
 pub mod actions;
 
-use crate::{Scene, SceneManager, data::{self, LeaderboardEntry}, generate_scene, math, scenes::game::actions::GameUpdateResult, theme};
+use crate::{Scene, SceneManager, data, generate_scene, math, scenes::{SaveBrowserMode, game::actions::GameUpdateResult}, theme};
 use actions::{
     GameData, PlayerAction, 
 };
@@ -58,6 +58,7 @@ widget_ids! {
         enemy_healthbar_text,
 
         enemy_text_name,
+        enemy_text_elite,
         enemy_text_status,
         enemy_image,
 
@@ -180,17 +181,32 @@ impl Scene for Game {
             scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
         }
 
-        if let Some(result) = game_data.update() {
+        let update_result = game_data.update();
+        game_data.autosave_if_needed();
+        if let Some(result) = update_result {
             match result {
                 GameUpdateResult::PlayerKilled => {
-                    let leaderboard_entry = LeaderboardEntry::new(String::from(game_data.player.borrow().name.clone()), game_data.enemies_killed.borrow().clone(), game_data.difficulty_settings.difficulty.clone());
-                    data_store.set("leaderboard_entry", leaderboard_entry);
+                    game_data.end_run();
+                    data_store.set("leaderboard_entry", game_data.leaderboard_entry());
+                    if let Some(taunt) = game_data.final_taunt() {
+                        data_store.set("final_taunt", taunt);
+                    }
                     data_store.remove("game_data");
                     self.next_scene_index = Some(SceneManager::GAME_OVER);
                     scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
                     return;
                 }
-                _ => {}
+                GameUpdateResult::EnemyKilled => {
+                    data_store.remove("player_health_current");
+                    data_store.remove("enemy_health_current");
+                    // The interlude goes on to the map once the player has picked an option
+                    self.next_scene_index = match game_data.difficulty_settings.interlude_options.is_empty() {
+                        true => Some(SceneManager::MAP),
+                        false => Some(SceneManager::INTERLUDE),
+                    };
+                    scene_manager.wake_up_events_loop().unwrap_or_else(|e|eprintln!("Failed to wake up events loop: {}", e));
+                    return;
+                }
             }
         }
         
@@ -393,14 +409,23 @@ impl Scene for Game {
             }
         }
 
-        if base_button.clone()
+        let can_player_heal = game_data.player.borrow().can_heal();
+        let mut heal_button = base_button.clone()
             .label("HEAL")
             .x_place_on(ids.player_container, Place::Start(Some(image_size + PANEL_ELEMENT_MARGIN * 2.0)))
-            .y_place_on(ids.player_container, Place::End(Some(healthbar_height + stat_height + button_height * 2.0 + PANEL_ELEMENT_MARGIN * 4.0)))
+            .y_place_on(ids.player_container, Place::End(Some(healthbar_height + stat_height + button_height * 2.0 + PANEL_ELEMENT_MARGIN * 4.0)));
+        if !can_player_heal {
+            heal_button = heal_button
+                .color(theme.button_disabled)
+                .hover_color(theme.button_disabled)
+                .press_color(theme.button_disabled);
+        }
+
+        if heal_button
             .set(ids.player_act_heal, ui)
             .was_clicked()
         {
-            if !is_player_disabled {
+            if !is_player_disabled && can_player_heal {
                 game_data.player_act(PlayerAction::Heal);
             }
         }
@@ -424,14 +449,24 @@ impl Scene for Game {
             .y_place_on(ids.enemy_container, Place::End(Some(-44.0)))
             .set(ids.enemy_text_status, ui);
 
+        let is_enemy_elite = game_data.enemy.borrow().is_elite();
         widget::Text::new(&game_data.enemy.borrow().name)
-            .color(theme.text_primary)
+            .color(match is_enemy_elite { true => theme.accent_color, false => theme.text_primary })
             .font_size(32)
             .font_id(*fonts.get("lato").unwrap())
             .x_align_to(ids.enemy_container, Align::End)
             .y_place_on(ids.enemy_container, Place::End(Some(-44.0)))
             .set(ids.enemy_text_name, ui);
 
+        if is_enemy_elite {
+            widget::Text::new(&format!("ELITE: {}", game_data.enemy.borrow().affix_names().join(", ")))
+                .color(theme.accent_color)
+                .font_size(20)
+                .font_id(*fonts.get("lato").unwrap())
+                .left_from(ids.enemy_text_name, 16.0)
+                .set(ids.enemy_text_elite, ui);
+        }
+
         widget::Image::new(*enemy_image_id)
             .w_h(image_size, image_size)
             .x_place_on(ids.enemy_container, Place::Start(Some(PANEL_ELEMENT_MARGIN)))
@@ -523,20 +558,27 @@ impl Scene for Game {
         .set(ids.enemy_stat_stamina_container, ui);
         
         if base_button.clone()
-            .label("Flee Battle")
+            .label(match game_data.ironman { true => "Flee (ends run)", false => "Flee Battle" })
             .w_h(256.0, 48.0)
             .x_align_to(ids.player_container, Align::Start)
             .y_place_on(ids.root, Place::End(Some(32.0)))
             .set(ids.button_flee, ui)
             .was_clicked()
         {
-            let file = std::fs::File::create(crate::ASSETS_FOLDER.join("data/runtime/current_game.yaml")).unwrap();
-            serde_yaml::to_writer(file, game_data).unwrap();
-
-            data_store.remove("game_data");
-            data_store.remove("player_health_current");
-            data_store.remove("enemy_health_current");
-            self.next_scene_index = Some(SceneManager::MAIN_MENU);
+            // Ironman runs can't be saved, fleeing ends them with the score so far
+            if game_data.ironman {
+                game_data.end_run();
+                data_store.set("leaderboard_entry", game_data.leaderboard_entry());
+                data_store.set("fled", ());
+                data_store.remove("game_data");
+                data_store.remove("player_health_current");
+                data_store.remove("enemy_health_current");
+                self.next_scene_index = Some(SceneManager::GAME_OVER);
+                return;
+            } else {
+                data_store.set("save_browser_mode", SaveBrowserMode::Save);
+                self.next_scene_index = Some(SceneManager::SAVE_BROWSER);
+            }
         }
 
         console (
@@ -1,10 +1,13 @@
 use std::{cell::RefCell, time::{Duration, Instant}};
 use rand::Rng;
 use serde::{Serialize, Deserialize};
-use crate::data::{self, CharacterStats, DifficultySettings};
+use crate::data::{self, Affix, AffixEffect, BalanceSettings, CharacterStats, DialoguePool, DialogueTrigger, DifficultySettings, EventChoice, EventOutcome, InterludeEffect, InterludeOption, LeaderboardEntry, ModifierEffects, NodeKind, RunMap, RunModifier, Autosave, SaveMetadata, SaveSlot, ShopItem};
 
-pub const HEALTH_MULTIPLIER: i32 = 5;
 pub const STATUS_EFFECT_TIME: Duration = Duration::from_secs_f64(1.5);
+/* Turns an enemy has to wait before its everyday lines show up in the console again */
+const DIALOGUE_LOG_COOLDOWN: u32 = 3;
+/* Enemies say their low health line once they drop below this share of max health */
+const LOW_HEALTH_SHARE: f64 = 0.3;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Turn {
@@ -41,17 +44,63 @@ pub struct Character {
     pub health: i32,
     pub stats: CharacterStats,
     pub difficulty_settings: DifficultySettings,
+    #[serde(default)]
+    pub combat_modifiers: CombatModifiers,
+    /* Only elite enemies have affixes */
+    #[serde(default)]
+    pub affixes: Vec<Affix>,
+    /* Heroes this enemy had slain when it came back as a nemesis, 0 otherwise */
+    #[serde(default)]
+    pub nemesis_kills: u32,
+    #[serde(default)]
+    pub training: Option<Training>,
+    /* Names of the items found in events, their stats are already part of `stats` */
+    #[serde(default)]
+    pub items: Vec<String>,
     is_player: bool,
     
     #[serde(skip)]
     rng: RefCell<rand::rngs::ThreadRng>,
 }
 
+/* The parts of the active run modifiers that apply to a single character */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombatModifiers {
+    pub attack_multiplier: f64,
+    pub health_multiplier: f64,
+    pub heal_multiplier: f64,
+    pub lifesteal: f64,
+}
+
+/* What the current enemy has said, so it doesn't repeat itself or flood the console */
+#[derive(Debug, Clone, Default)]
+struct DialogueState {
+    last_line: Option<String>,
+    turns_since_logged: u32,
+    said_low_health: bool,
+    /* The enemy's line after killing the player, shown on the game over screen */
+    final_taunt: Option<String>,
+}
+
+/* A temporary attack buff from training between battles */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Training {
+    pub attack_multiplier: f64,
+    pub battles_left: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameData {
     pub turn: RefCell<Turn>,
     
     pub enemies_killed: RefCell<u32>,
+    /* Extra score from elite kills on top of one point per kill */
+    #[serde(default)]
+    pub bonus_score: RefCell<u32>,
+    /* Score paid to merchants, taken off the total */
+    #[serde(default)]
+    score_spent: RefCell<u32>,
     pub is_player_focused: RefCell<bool>,
 
     pub waiting_for_player: RefCell<bool>,
@@ -61,8 +110,38 @@ pub struct GameData {
     pub enemy_status_text: RefCell<String>,
 
     pub difficulty_settings: DifficultySettings,
+    #[serde(default)]
+    pub modifiers: Vec<RunModifier>,
+    /* Mods active when the run started, so a continued run is still ranked with them */
+    #[serde(default)]
+    pub mods: Vec<String>,
+    /* Links the autosave and manual saves of one run; older saves get a fresh id */
+    #[serde(default = "rand::random")]
+    pub run_id: u64,
+    /* Saves from before the map existed load without one and get a fresh map */
+    #[serde(default)]
+    pub map: RefCell<Option<RunMap>>,
+    /* Every decision made so far chained together, signed into the leaderboard entry */
+    #[serde(default)]
+    replay_hash: RefCell<String>,
+    /* Set for good once the run is loaded from a save that failed verification */
+    #[serde(default)]
+    unverified: bool,
+    /* Ironman runs only keep the autosave, and leaving one ends it */
+    #[serde(default)]
+    pub ironman: bool,
 
     info_text: RefCell<Vec<String>>,
+    #[serde(skip)]
+    dialogue: RefCell<DialogueState>,
+    /* Time played before the current session, the rest is counted from `session_start` */
+    #[serde(default)]
+    playtime_secs: RefCell<u64>,
+    #[serde(skip, default = "default_instant")]
+    session_start: RefCell<Instant>,
+    /* Set whenever a turn or a choice between battles changes the run */
+    #[serde(skip)]
+    needs_autosave: RefCell<bool>,
 
     #[serde(skip, default = "default_instant")]
     pub wait_for_enemy_timer: RefCell<Instant>,
@@ -99,9 +178,14 @@ impl Character {
             name,
             character_type,
             state: CharacterState::Idle,
-            health: stats.vitality * HEALTH_MULTIPLIER,
+            health: BalanceSettings::balance_settings().max_health(&stats),
             stats,
             difficulty_settings,
+            combat_modifiers: CombatModifiers::default(),
+            affixes: Vec::new(),
+            nemesis_kills: 0,
+            training: None,
+            items: Vec::new(),
             is_player: true,
             rng: RefCell::new(rand::thread_rng()),
         }
@@ -112,36 +196,126 @@ impl Character {
             name: self.name,
             character_type: self.character_type,
             state: CharacterState::Idle,
-            health: self.stats.vitality * HEALTH_MULTIPLIER,
+            health: BalanceSettings::balance_settings().max_health(&self.stats),
             stats: self.stats,
             difficulty_settings: self.difficulty_settings,
+            combat_modifiers: self.combat_modifiers,
+            affixes: self.affixes,
+            nemesis_kills: self.nemesis_kills,
+            training: self.training,
+            items: self.items,
             is_player: false,
             rng: self.rng,
         }
     }
 
+    /* Applies run modifiers and starts the character off at their new max health */
+    pub fn with_combat_modifiers(mut self, combat_modifiers: CombatModifiers) -> Self {
+        self.combat_modifiers = combat_modifiers;
+        self.health = self.get_max_health();
+        self
+    }
+
+    pub fn with_affixes(mut self, affixes: Vec<Affix>) -> Self {
+        self.affixes = affixes;
+        self
+    }
+
+    pub fn as_nemesis(mut self, kills: u32) -> Self {
+        self.nemesis_kills = kills;
+        self
+    }
+
+    pub fn is_nemesis(&self) -> bool {
+        self.nemesis_kills > 0
+    }
+
+    pub fn is_elite(&self) -> bool {
+        !self.affixes.is_empty()
+    }
+
+    pub fn affix_names(&self) -> Vec<&str> {
+        self.affixes.iter().map(|affix| affix.name.as_str()).collect()
+    }
+
+    /* Score for killing this character, elites are worth more */
+    pub fn kill_score_bonus(&self) -> u32 {
+        self.affixes.iter().map(|affix| affix.score_bonus).sum()
+    }
+
     pub fn get_max_health(&self) -> i32 {
-        self.stats.vitality * HEALTH_MULTIPLIER
+        let max_health = BalanceSettings::balance_settings().max_health(&self.stats);
+        scale(max_health, self.combat_modifiers.health_multiplier).max(1)
     }
 
     pub fn get_attack_power(&self) -> i32 {
-        let mut power = self.rng.borrow_mut().gen_range(self.stats.attack-2..=self.stats.attack+2);
-        if power <= 0 {
-            power = 1;
-        }
-        power
+        let power = self.roll_power(BalanceSettings::balance_settings().attack_range(&self.stats));
+        let training_multiplier = self.training.map_or(1.0, |training| training.attack_multiplier);
+        scale(power, self.combat_modifiers.attack_multiplier * self.rage_multiplier() * training_multiplier).max(1)
     }
 
     pub fn get_defense_power(&self) -> i32 {
-        let mut power = self.rng.borrow_mut().gen_range(self.stats.defense-2..=self.stats.defense+2);
-        if power <= 0 {
-            power = 1;
-        }
-        power
+        self.roll_power(BalanceSettings::balance_settings().defense_range(&self.stats))
     }
 
     pub fn get_heal_power(&self) -> i32 {
-        let mut power = self.rng.borrow_mut().gen_range(self.stats.stamina-5..=self.stats.stamina+5);
+        let power = self.roll_power(BalanceSettings::balance_settings().heal_range(&self.stats));
+        scale(power, self.combat_modifiers.heal_multiplier)
+    }
+
+    pub fn can_heal(&self) -> bool {
+        self.combat_modifiers.heal_multiplier > 0.0
+    }
+
+    fn rage_multiplier(&self) -> f64 {
+        let max_health = self.get_max_health() as f64;
+        self.affixes.iter().fold(1.0, |multiplier, affix| match affix.effect {
+            AffixEffect::Enraged { below_health, attack_multiplier } if (self.health as f64) < max_health * below_health => multiplier * attack_multiplier,
+            _ => multiplier,
+        })
+    }
+
+    /* Called when a battle is won, training wears off after a few of them */
+    pub fn on_battle_won(&mut self) {
+        if let Some(training) = &mut self.training {
+            training.battles_left = training.battles_left.saturating_sub(1);
+            if training.battles_left == 0 {
+                self.training = None;
+            }
+        }
+    }
+
+    /* Called after this character is hit. Returns how much damage is reflected at the attacker. */
+    pub fn on_damage_taken(&self, damage: i32) -> i32 {
+        self.affixes.iter().map(|affix| match affix.effect {
+            AffixEffect::Thorns { reflect } => scale(damage, reflect),
+            _ => 0,
+        }).sum()
+    }
+
+    /* Called before this character acts. Returns how much health was regenerated. */
+    pub fn on_turn_start(&mut self) -> i32 {
+        let max_health = self.get_max_health();
+        let regenerated: i32 = self.affixes.iter().map(|affix| match affix.effect {
+            AffixEffect::Regenerating { heal_per_turn } => scale(max_health, heal_per_turn),
+            _ => 0,
+        }).sum();
+        let regenerated = regenerated.min(max_health - self.health).max(0);
+        self.health += regenerated;
+        regenerated
+    }
+
+    /* Called after this character lands a hit. Returns how much health was stolen back. */
+    pub fn on_damage_dealt(&mut self, damage: i32) -> i32 {
+        let stolen = scale(damage, self.combat_modifiers.lifesteal);
+        if stolen > 0 {
+            self.heal(stolen);
+        }
+        stolen
+    }
+
+    fn roll_power(&self, (min, max): (i32, i32)) -> i32 {
+        let mut power = self.rng.borrow_mut().gen_range(min..=max);
         if power <= 0 {
             power = 1;
         }
@@ -153,6 +327,10 @@ impl Character {
             true => self.difficulty_settings.player_evade_chance,
             false => self.difficulty_settings.enemy_evade_chance,
         };
+        let chance = self.affixes.iter().fold(chance, |chance, affix| match affix.effect {
+            AffixEffect::Evasive { evade_chance } => chance + evade_chance,
+            _ => chance,
+        }).clamp(0.0, 1.0);
 
         let mut evaded = false;
         for _ in 0..self.get_defense_power() {
@@ -170,13 +348,17 @@ impl Character {
             return true;
         }
 
-        self.health -= damage;
+        self.lose_health(damage);
         println!("{} took {} damage", self.name, damage);
+        false
+    }
+
+    /* Damage that can't be evaded, such as thorns */
+    pub fn lose_health(&mut self, damage: i32) {
+        self.health -= damage;
         if self.health <= 0 {
             self.health = 0;
         }
-
-        false
     }
 
     pub fn heal(&mut self, heal_amount: i32) {
@@ -188,19 +370,52 @@ impl Character {
     }
 }
 
+impl CombatModifiers {
+    pub fn for_player(effects: &ModifierEffects) -> Self {
+        CombatModifiers {
+            attack_multiplier: effects.player_attack_multiplier,
+            health_multiplier: effects.player_health_multiplier,
+            heal_multiplier: effects.player_heal_multiplier,
+            lifesteal: effects.player_lifesteal,
+        }
+    }
+
+    pub fn for_enemy(effects: &ModifierEffects) -> Self {
+        CombatModifiers {
+            health_multiplier: effects.enemy_health_multiplier,
+            ..CombatModifiers::default()
+        }
+    }
+}
+
+impl Default for CombatModifiers {
+    fn default() -> Self {
+        CombatModifiers {
+            attack_multiplier: 1.0,
+            health_multiplier: 1.0,
+            heal_multiplier: 1.0,
+            lifesteal: 0.0,
+        }
+    }
+}
+
 impl GameData {
-    pub fn new(player: Character, difficulty_settings: DifficultySettings) -> GameData {
+    pub fn new(player: Character, difficulty_settings: DifficultySettings, modifiers: Vec<RunModifier>, seed: u64, ironman: bool) -> GameData {
         let mut rng = rand::thread_rng();
         let turn = match rng.gen_bool(0.5) {
             true => Turn::Enemy,
             false => Turn::Player,
         };
 
-        let enemy = GameData::make_enemy(difficulty_settings);
+        let effects = RunModifier::combined_effects(&modifiers);
+        let player = player.with_combat_modifiers(CombatModifiers::for_player(&effects));
+        let enemy = GameData::make_enemy(&difficulty_settings, &effects, 0);
 
         let data = GameData {
             turn: RefCell::new(turn.clone()),
             enemies_killed: RefCell::new(0),
+            bonus_score: RefCell::new(0),
+            score_spent: RefCell::new(0),
             is_player_focused: RefCell::new(false),
 
             player_status_text: RefCell::new(match turn { Turn::Player => "Thinking...", Turn::Enemy => "" }.to_string()),
@@ -210,8 +425,19 @@ impl GameData {
             waiting_for_enemy: RefCell::new(false),
 
             difficulty_settings,
+            modifiers,
+            mods: data::Mod::active_ids(),
+            run_id: rand::random(),
+            map: RefCell::new(Some(RunMap::generate(seed, 1))),
+            replay_hash: RefCell::new(String::new()),
+            unverified: false,
+            ironman,
 
             info_text: RefCell::new(Vec::new()),
+            dialogue: RefCell::new(DialogueState { turns_since_logged: DIALOGUE_LOG_COOLDOWN, ..DialogueState::default() }),
+            playtime_secs: RefCell::new(0),
+            session_start: RefCell::new(Instant::now()),
+            needs_autosave: RefCell::new(false),
 
             wait_for_enemy_timer: RefCell::new(Instant::now()),
             player_status_timer: RefCell::new(Instant::now()),
@@ -224,7 +450,8 @@ impl GameData {
             rng: RefCell::new(rng),
         };
         
-        data.add_info_text(format!("~===== A wild {} appeared! =====~", data.enemy.borrow().name));
+        data.announce_enemy();
+        data.enemy_greet();
 
         data
     }
@@ -251,7 +478,8 @@ impl GameData {
                 *self.enemy_status_text.borrow_mut() = "...Thinking".to_string();
             }
 
-            *self.wait_for_enemy_timer.borrow_mut() = Instant::now() + Duration::from_secs_f64(self.rng.borrow_mut().gen_range(1.0..4.0));
+            let think_time = self.rng.borrow_mut().gen_range(1.0..4.0) * self.modifier_effects().enemy_think_time_multiplier;
+            *self.wait_for_enemy_timer.borrow_mut() = Instant::now() + Duration::from_secs_f64(think_time);
         }
 
 
@@ -289,13 +517,33 @@ impl GameData {
             println!("Enemy is dead!");
             let kills = self.enemies_killed.borrow().clone();
             *self.enemies_killed.borrow_mut() = kills + 1;
-            *self.enemy.borrow_mut() = Box::new(GameData::make_enemy(self.difficulty_settings));
-            self.add_info_text(format!("~===== A wild {} appeared! =====~", self.enemy.borrow().name));
+            let bonus = self.enemy.borrow().kill_score_bonus();
+            *self.bonus_score.borrow_mut() += bonus;
+            if self.enemy.borrow().is_nemesis() {
+                self.record_nemesis_defeat();
+            }
+            self.player.borrow_mut().on_battle_won();
+            *self.enemy.borrow_mut() = Box::new(GameData::make_enemy(&self.difficulty_settings, &self.modifier_effects(), kills + 1));
+            self.dialogue.borrow_mut().said_low_health = false;
+            self.announce_enemy();
+            self.enemy_greet();
             return Some(GameUpdateResult::EnemyKilled);
         }
 
         if self.player.borrow().health <= 0 {
             println!("Player is dead!");
+            if let Some(line) = self.enemy_line(DialogueTrigger::KillPlayer) {
+                *self.enemy_status_text.borrow_mut() = line.clone();
+                self.dialogue.borrow_mut().final_taunt = Some(format!("{}: \"{}\"", self.enemy.borrow().name, line));
+            }
+            let killer = self.enemy.borrow();
+            data::Nemesis::record_kill(data::Nemesis::new(
+                killer.name.clone(),
+                killer.character_type.clone(),
+                killer.stats,
+                killer.affixes.clone(),
+                self.difficulty_settings.difficulty.clone(),
+            ));
             return Some(GameUpdateResult::PlayerKilled);
         }
 
@@ -303,6 +551,7 @@ impl GameData {
     }
     
     pub fn player_act(&self, action: PlayerAction) {
+        self.record_decision(format!("{:?}", action));
         match action {
             PlayerAction::Attack => {
                 self.player_act_attack();
@@ -330,6 +579,264 @@ impl GameData {
         *self.turn.borrow() == Turn::Enemy
     }
 
+    /*
+        Moves to `position` on the map. Elite and boss nodes toughen up the
+        enemy that was already waiting, so scouting it stays worthwhile.
+    */
+    pub fn enter_map_node(&self, position: (usize, usize)) -> Result<NodeKind, String> {
+        let kind = match &mut *self.map.borrow_mut() {
+            Some(map) => map.move_to(position)?,
+            None => return Err(String::from("This run has no map")),
+        };
+
+        *self.needs_autosave.borrow_mut() = true;
+        self.record_decision(format!("Map {} {}", position.0, position.1));
+        let mut rng = rand::thread_rng();
+        match kind {
+            NodeKind::Elite if !self.enemy.borrow().is_elite() => {
+                let mut enemy = self.enemy.borrow_mut();
+                enemy.affixes = Affix::roll(&mut rng);
+                drop(enemy);
+                self.announce_enemy();
+            }
+            NodeKind::Boss => {
+                let mut enemy = self.enemy.borrow_mut();
+                enemy.stats = enemy.stats + CharacterStats::random(&mut rng, self.difficulty_settings.enemy_base_attribute_points);
+                if !enemy.is_elite() {
+                    enemy.affixes = Affix::roll(&mut rng);
+                }
+                enemy.health = enemy.get_max_health();
+                let text = format!("~===== {} guards the end of act {}! =====~", enemy.name, self.map_act());
+                drop(enemy);
+                self.add_info_text(text);
+            }
+            _ => {}
+        }
+        Ok(kind)
+    }
+
+    /* Starts the next act once the boss has fallen, or a map for saves that had none */
+    pub fn ensure_map(&self, seed: u64) {
+        let mut map = self.map.borrow_mut();
+        match &*map {
+            None => *map = Some(RunMap::generate(seed, 1)),
+            Some(current) if current.is_finished() => *map = Some(current.next_act()),
+            Some(_) => {}
+        }
+    }
+
+    /* The seed the run's first map was made from, 0 for saves from before the map existed */
+    pub fn seed(&self) -> u64 {
+        match &*self.map.borrow() {
+            Some(map) => map.seed.wrapping_sub(map.act as u64 - 1),
+            None => 0,
+        }
+    }
+
+    pub fn replay_hash(&self) -> String {
+        self.replay_hash.borrow().clone()
+    }
+
+    /* False if any save this run was loaded from had been changed outside the game */
+    pub fn is_verified(&self) -> bool {
+        !self.unverified
+    }
+
+    fn record_decision(&self, decision: String) {
+        let hash = data::chain_replay_hash(&self.replay_hash.borrow(), &decision);
+        *self.replay_hash.borrow_mut() = hash;
+    }
+
+    fn map_act(&self) -> u32 {
+        self.map.borrow().as_ref().map_or(1, |map| map.act)
+    }
+
+    /* Applies an option picked between battles and describes what happened */
+    pub fn apply_interlude_option(&self, option: &InterludeOption) -> String {
+        *self.needs_autosave.borrow_mut() = true;
+        self.record_decision(format!("Interlude {}", option.id));
+        let mut player = self.player.borrow_mut();
+        match option.effect {
+            InterludeEffect::Rest { heal } => {
+                let before = player.health;
+                let amount = scale(player.get_max_health(), heal);
+                player.heal(amount);
+                format!("You rest and recover {} health.", player.health - before)
+            }
+            InterludeEffect::Train { attack_multiplier, battles } => {
+                player.training = Some(Training { attack_multiplier, battles_left: battles });
+                format!("You train hard. Your attacks are {:.0}% stronger for {} battle(s).", (attack_multiplier - 1.0) * 100.0, battles)
+            }
+            InterludeEffect::Scout => {
+                let enemy = self.enemy.borrow();
+                format!("You scout ahead and spot {}.", enemy.name)
+            }
+        }
+    }
+
+    /*
+        Applies the outcomes of a choice made in an event and describes them.
+        The second value is true when the choice forces a battle.
+    */
+    pub fn apply_event_choice(&self, choice: &EventChoice) -> (String, bool) {
+        *self.needs_autosave.borrow_mut() = true;
+        self.record_decision(format!("Event {}", choice.text));
+        let mut lines = vec![choice.result.clone()];
+        let battle = self.apply_outcomes(&choice.outcomes, &mut lines);
+        (lines.join("\n"), battle)
+    }
+
+    /* Pays for an item from a merchant and describes what it did, or why it couldn't be bought */
+    pub fn buy(&self, item: &ShopItem) -> Result<String, String> {
+        if self.score() < item.price {
+            return Err(format!("You can't afford the {}.", item.name));
+        }
+        *self.needs_autosave.borrow_mut() = true;
+        self.record_decision(format!("Shop {}", item.id));
+        *self.score_spent.borrow_mut() += item.price;
+        let mut lines = vec![format!("You buy the {} for {} score.", item.name, item.price)];
+        self.apply_outcomes(&item.outcomes, &mut lines);
+        Ok(lines.join("\n"))
+    }
+
+    /* Describes each outcome in `lines`, returns true if one of them forces a battle */
+    fn apply_outcomes(&self, outcomes: &[EventOutcome], lines: &mut Vec<String>) -> bool {
+        let mut player = self.player.borrow_mut();
+        let mut battle = false;
+        for outcome in outcomes {
+            match outcome {
+                EventOutcome::Health { amount } => {
+                    let before = player.health;
+                    let change = scale(player.get_max_health(), amount.abs());
+                    match *amount < 0.0 {
+                        true => player.health = (player.health - change).max(1),
+                        false => player.heal(change),
+                    }
+                    match player.health - before {
+                        0 => {}
+                        difference if difference > 0 => lines.push(format!("You recover {} health.", difference)),
+                        difference => lines.push(format!("You lose {} health.", -difference)),
+                    }
+                }
+                EventOutcome::Stats { stats } => {
+                    player.stats = player.stats + *stats;
+                    lines.push(describe_stats(stats));
+                }
+                EventOutcome::Item { name, stats } => {
+                    player.stats = player.stats + *stats;
+                    player.items.push(name.clone());
+                    lines.push(format!("You now carry the {}. {}", name, describe_stats(stats)));
+                }
+                EventOutcome::Battle => battle = true,
+            }
+        }
+        // Losing vitality can leave the player above their new max health
+        player.health = player.health.min(player.get_max_health());
+        battle
+    }
+
+    /* How far into the run the player is, used to pick events */
+    pub fn depth(&self) -> u32 {
+        match &*self.map.borrow() {
+            Some(map) => map.depth(),
+            None => *self.enemies_killed.borrow(),
+        }
+    }
+
+    pub fn playtime_secs(&self) -> u64 {
+        *self.playtime_secs.borrow() + self.session_start.borrow().elapsed().as_secs()
+    }
+
+    pub fn save_metadata(&self, name: String) -> SaveMetadata {
+        let player = self.player.borrow();
+        SaveMetadata {
+            name,
+            character_name: player.name.clone(),
+            character_type: player.character_type.clone(),
+            difficulty: self.difficulty_settings.difficulty.clone(),
+            custom: self.difficulty_settings.custom,
+            kills: *self.enemies_killed.borrow(),
+            playtime_secs: self.playtime_secs(),
+            saved_at: 0,
+            run_id: self.run_id,
+            finished: false,
+            autosave_number: 0,
+        }
+    }
+
+    /* Folds the current session into the stored playtime so it is saved with the run */
+    fn checkpoint_playtime(&self) {
+        let playtime = self.playtime_secs();
+        *self.playtime_secs.borrow_mut() = playtime;
+        *self.session_start.borrow_mut() = Instant::now();
+    }
+
+    pub fn save_to_slot(&self, index: usize, name: String) -> Result<(), String> {
+        if self.ironman {
+            return Err(String::from("Ironman runs can't be saved by hand"));
+        }
+        self.checkpoint_playtime();
+        SaveSlot::write(index, &self.save_metadata(name), self)
+    }
+
+    pub fn load_from_slot(index: usize) -> Result<GameData, String> {
+        SaveSlot::read(index).map(|(game_data, verified)| GameData::resumed(game_data, verified))
+    }
+
+    pub fn load_autosave() -> Result<GameData, String> {
+        Autosave::read().map(|(game_data, verified)| GameData::resumed(game_data, verified))
+    }
+
+    fn resumed(mut game_data: GameData, verified: bool) -> GameData {
+        game_data.unverified |= !verified;
+        game_data.player.borrow_mut().state = CharacterState::Idle;
+        game_data.enemy.borrow_mut().state = CharacterState::Idle;
+        game_data
+    }
+
+    /* Writes the autosave if anything happened since the last one */
+    pub fn autosave_if_needed(&self) {
+        if !self.needs_autosave.replace(false) {
+            return;
+        }
+        self.checkpoint_playtime();
+        if let Err(error) = Autosave::write(&self.save_metadata(String::from("Autosave")), self) {
+            eprintln!("{}", error);
+        }
+    }
+
+    /* Called once the player dies or an ironman run is left, nothing of the run can be continued */
+    pub fn end_run(&self) {
+        if let Err(error) = Autosave::clear() {
+            eprintln!("{}", error);
+        }
+        SaveSlot::finish_run(self.run_id);
+    }
+
+    /* The run's leaderboard entry as it stands, signed unless it was loaded from an edited save */
+    pub fn leaderboard_entry(&self) -> LeaderboardEntry {
+        let player = self.player.borrow();
+        let score = RunModifier::apply_score_multiplier(self.score(), &self.modifiers);
+        let entry = LeaderboardEntry::new(player.name.clone(), score, self.difficulty_settings.difficulty.clone(), self.difficulty_settings.custom)
+            .with_modifiers(&self.modifiers)
+            .with_mods(self.mods.clone())
+            .with_run(player.character_type.clone(), *self.enemies_killed.borrow())
+            .with_replay(self.seed(), self.replay_hash());
+        match self.is_verified() {
+            true => entry.signed(),
+            false => entry,
+        }
+    }
+
+    /* One point per kill plus elite bonuses, less what was spent in shops, before run modifiers */
+    pub fn score(&self) -> u32 {
+        (*self.enemies_killed.borrow() + *self.bonus_score.borrow()).saturating_sub(*self.score_spent.borrow())
+    }
+
+    pub fn modifier_effects(&self) -> ModifierEffects {
+        RunModifier::combined_effects(&self.modifiers)
+    }
+
     pub fn get_info_text(&self) -> &Vec<String> {
         unsafe {&*self.info_text.as_ptr()}
     }
@@ -360,6 +867,16 @@ impl GameData {
             self.enemy.borrow_mut().state = CharacterState::Hurt;
             *self.enemy_state_timer.borrow_mut() = Instant::now() + Duration::from_secs_f64(self.rng.borrow_mut().gen_range(1.0..2.5));
             self.add_info_text(format!("{} (you) attacked {} for {} damage!", self.player.borrow().name, self.enemy.borrow().name, attack_power));
+            let stolen = self.player.borrow_mut().on_damage_dealt(attack_power);
+            if stolen > 0 {
+                self.add_info_text(format!("{} (you) drained {} health.", self.player.borrow().name, stolen));
+            }
+            let reflected = self.enemy.borrow().on_damage_taken(attack_power);
+            if reflected > 0 {
+                self.player.borrow_mut().lose_health(reflected);
+                self.add_info_text(format!("{}'s thorns hurt {} (you) for {} damage!", self.enemy.borrow().name, self.player.borrow().name, reflected));
+            }
+            self.check_enemy_low_health();
         }
 
         if is_player_focused {
@@ -369,6 +886,11 @@ impl GameData {
     }
 
     fn player_act_heal(&self) {
+        if !self.player.borrow().can_heal() {
+            self.add_info_text(format!("{} (you) can't heal during this run!", self.player.borrow().name));
+            return;
+        }
+
         let mut heal_power = self.player.borrow().get_heal_power();
         let is_player_focused = self.is_player_focused.borrow().clone();
         if is_player_focused {
@@ -393,37 +915,48 @@ impl GameData {
     }
 
     fn enemy_act(&self) {
-        let chance: f64 = self.rng.borrow_mut().gen_range(0.0 .. 1.0);
-        if chance < self.difficulty_settings.enemy_attack_chance {
+        let regenerated = self.enemy.borrow_mut().on_turn_start();
+        if regenerated > 0 {
+            self.add_info_text(format!("{} regenerated {} health.", self.enemy.borrow().name, regenerated));
+        }
+
+        let attack_chance = self.difficulty_settings.enemy_attack_chance;
+        let heal_chance = self.difficulty_settings.enemy_heal_chance;
+        let do_nothing_chance = self.difficulty_settings.enemy_do_nothing_chance;
+        let chance: f64 = self.rng.borrow_mut().gen_range(0.0 .. 1.0) * (attack_chance + heal_chance + do_nothing_chance);
+        if chance < attack_chance {
             self.enemy.borrow_mut().state = CharacterState::Attack;
             *self.enemy_state_timer.borrow_mut() = Instant::now() + Duration::from_secs_f64(self.rng.borrow_mut().gen_range(1.0..2.5));
 
             let attack_power = self.enemy.borrow().get_attack_power();
             let evaded = self.player.borrow_mut().take_damage(attack_power);
             if evaded {
-                *self.enemy_status_text.borrow_mut() = "Missed!".to_string();
+                *self.enemy_status_text.borrow_mut() = self.enemy_line(DialogueTrigger::Miss).unwrap_or_else(|| "Missed!".to_string());
                 self.add_info_text(format!("{} tried to attack {} (you), but missed!", self.enemy.borrow().name, self.player.borrow().name));
             } else {
                 self.player.borrow_mut().state = CharacterState::Hurt;
                 *self.player_state_timer.borrow_mut() = Instant::now() + Duration::from_secs_f64(self.rng.borrow_mut().gen_range(1.0..2.5));
-                *self.enemy_status_text.borrow_mut() = "Attacking!".to_string();
+                *self.enemy_status_text.borrow_mut() = self.enemy_line(DialogueTrigger::Hit).unwrap_or_else(|| "Attacking!".to_string());
                 self.add_info_text(format!("{} attacked {} (you) for {} damage!", self.enemy.borrow().name, self.player.borrow().name, attack_power));
+                self.enemy.borrow_mut().on_damage_dealt(attack_power);
             }
             *self.enemy_status_timer.borrow_mut() = Instant::now() + STATUS_EFFECT_TIME;
-        } else if chance < self.difficulty_settings.enemy_attack_chance + self.difficulty_settings.enemy_heal_chance {
+        } else if chance < attack_chance + heal_chance {
             let heal_power = self.enemy.borrow().get_heal_power();
             self.enemy.borrow_mut().heal(heal_power);
 
-            *self.enemy_status_text.borrow_mut() = "Healing!".to_string();
+            *self.enemy_status_text.borrow_mut() = self.enemy_line(DialogueTrigger::Heal).unwrap_or_else(|| "Healing!".to_string());
             *self.enemy_status_timer.borrow_mut() = Instant::now() + STATUS_EFFECT_TIME;
             self.add_info_text(format!("{} healed for {}!", self.enemy.borrow().name, heal_power));
         } else {
-            *self.enemy_status_text.borrow_mut() = "Trembling in fear!".to_string();
+            *self.enemy_status_text.borrow_mut() = self.enemy_line(DialogueTrigger::Cower).unwrap_or_else(|| "Trembling in fear!".to_string());
             *self.enemy_status_timer.borrow_mut() = Instant::now() + STATUS_EFFECT_TIME;
         }
     }
 
     fn next_turn(&self) {
+        self.dialogue.borrow_mut().turns_since_logged += 1;
+        *self.needs_autosave.borrow_mut() = true;
         let current_turn = self.turn.borrow().clone();
         *self.turn.borrow_mut() = match current_turn {
             Turn::Player => Turn::Enemy,
@@ -431,6 +964,81 @@ impl GameData {
         };
     }
 
+    fn record_nemesis_defeat(&self) {
+        let enemy = self.enemy.borrow();
+        data::Nemesis::record_defeat(&enemy.name);
+        data::Profile::record_nemesis_defeat(data::DefeatedNemesis {
+            name: enemy.name.clone(),
+            kills: enemy.nemesis_kills,
+            defeated_by: self.player.borrow().name.clone(),
+            difficulty: self.difficulty_settings.difficulty.clone(),
+        });
+        self.add_info_text(format!("{} (you) avenged {} fallen heroes by defeating {}!", self.player.borrow().name, enemy.nemesis_kills, enemy.name));
+    }
+
+    fn announce_enemy(&self) {
+        let text = {
+            let enemy = self.enemy.borrow();
+            if enemy.is_nemesis() {
+                format!("~===== Your nemesis {} returns, stronger after slaying {} heroes! =====~", enemy.name, enemy.nemesis_kills)
+            } else if enemy.is_elite() {
+                format!("~===== An elite {} ({}) appeared! =====~", enemy.name, enemy.affix_names().join(", "))
+            } else {
+                format!("~===== A wild {} appeared! =====~", enemy.name)
+            }
+        };
+        self.add_info_text(text);
+    }
+
+    /*
+        Picks what the enemy says on `trigger`, or None if it keeps quiet. The
+        line is also written to the console, unless it's an everyday line and
+        the enemy already spoke there in the last few turns.
+    */
+    fn enemy_line(&self, trigger: DialogueTrigger) -> Option<String> {
+        let line = {
+            let enemy = self.enemy.borrow();
+            let last_line = self.dialogue.borrow().last_line.clone();
+            DialoguePool::line(&enemy.character_type, trigger, last_line.as_deref(), &mut *self.rng.borrow_mut())?.to_string()
+        };
+
+        let mut dialogue = self.dialogue.borrow_mut();
+        dialogue.last_line = Some(line.clone());
+        if trigger.is_important() || dialogue.turns_since_logged >= DIALOGUE_LOG_COOLDOWN {
+            dialogue.turns_since_logged = 0;
+            self.add_info_text(format!("{}: \"{}\"", self.enemy.borrow().name, line));
+        }
+        Some(line)
+    }
+
+    fn enemy_greet(&self) {
+        if let Some(line) = self.enemy_line(DialogueTrigger::Spawn) {
+            *self.enemy_status_text.borrow_mut() = line;
+            *self.enemy_status_timer.borrow_mut() = Instant::now() + STATUS_EFFECT_TIME;
+        }
+    }
+
+    fn check_enemy_low_health(&self) {
+        let is_low = {
+            let enemy = self.enemy.borrow();
+            enemy.health > 0 && (enemy.health as f64) < enemy.get_max_health() as f64 * LOW_HEALTH_SHARE
+        };
+        if !is_low || self.dialogue.borrow().said_low_health {
+            return;
+        }
+
+        self.dialogue.borrow_mut().said_low_health = true;
+        if let Some(line) = self.enemy_line(DialogueTrigger::LowHealth) {
+            *self.enemy_status_text.borrow_mut() = line;
+            *self.enemy_status_timer.borrow_mut() = Instant::now() + STATUS_EFFECT_TIME;
+        }
+    }
+
+    /* What the enemy said after landing the killing blow, if anything */
+    pub fn final_taunt(&self) -> Option<String> {
+        self.dialogue.borrow().final_taunt.clone()
+    }
+
     fn add_info_text(&self, text: String) {
         if self.info_text.borrow().len() == 10 {
             self.info_text.borrow_mut().remove(0);
@@ -439,24 +1047,50 @@ impl GameData {
         self.info_text.borrow_mut().push(text);
     }
 
-    fn make_enemy(difficulty_settings: DifficultySettings) -> Character {
+    fn make_enemy(difficulty_settings: &DifficultySettings, effects: &ModifierEffects, kills: u32) -> Character {
         let mut rng = rand::thread_rng();
-        let enemy_assigned_stats = data::CharacterStats::random(&mut rng, difficulty_settings.enemy_base_attribute_points);
-        let enemy_type_idx = rng.gen_range(0..=data::CHARACTER_TYPE_COUNT);
-        let (enemy_type, enemy_name) = match enemy_type_idx {
-            0 => ("adventurer", "Enemy adventurer"),
-            1 => ("female", "Enemy female"),
-            2 => ("player", "Enemy player"),
-            3 => ("soldier", "Enemy soldier"),
-            _ => ("zombie", "Enemy zombie"),
+        if let Some(nemesis) = data::Nemesis::maybe_return(&mut rng, &difficulty_settings.difficulty, kills) {
+            if data::CharacterType::all().iter().any(|character_type| character_type.id == nemesis.character_type) {
+                let stats = nemesis.empowered_stats(&mut rng);
+                return Character::new(nemesis.name, nemesis.character_type, stats, difficulty_settings.clone())
+                    .as_enemy()
+                    .as_nemesis(nemesis.kills)
+                    .with_affixes(nemesis.affixes)
+                    .with_combat_modifiers(CombatModifiers::for_enemy(effects));
+            }
+        }
+
+        let enemy_points = scale(difficulty_settings.enemy_base_attribute_points, effects.enemy_points_multiplier).max(1);
+        let enemy_assigned_stats = data::CharacterStats::random(&mut rng, enemy_points);
+        let spawn = data::SpawnEntry::spawn(&difficulty_settings.spawn_table, kills, &mut rng);
+        let enemy_stats = spawn.character_type.base_stats + enemy_assigned_stats;
+        let enemy_name = data::EnemyTitle::enemy_name(&mut rng, spawn.character_type, &enemy_stats, spawn.role.as_deref());
+        let affixes = match rng.gen_bool(difficulty_settings.elite_chance) {
+            true => Affix::roll(&mut rng),
+            false => Vec::new(),
         };
         
-        let enemy_stats = data::CharacterStats::base_character_stats()[enemy_type] + enemy_assigned_stats;
-        
-        Character::new(enemy_name.to_string(), enemy_type.to_string(), enemy_stats, difficulty_settings.clone()).as_enemy()
+        Character::new(enemy_name, spawn.character_type.id.clone(), enemy_stats, difficulty_settings.clone())
+            .as_enemy()
+            .with_affixes(affixes)
+            .with_combat_modifiers(CombatModifiers::for_enemy(effects))
     }
 }
 
+/* "Attack +1, Stamina -1" */
+fn describe_stats(stats: &CharacterStats) -> String {
+    let changes: Vec<String> = [("Vitality", stats.vitality), ("Attack", stats.attack), ("Defense", stats.defense), ("Stamina", stats.stamina)]
+        .iter()
+        .filter(|(_, value)| *value != 0)
+        .map(|(name, value)| format!("{} {:+}", name, value))
+        .collect();
+    changes.join(", ")
+}
+
+fn scale(value: i32, multiplier: f64) -> i32 {
+    (value as f64 * multiplier).round() as i32
+}
+
 fn default_instant() -> RefCell<Instant> {
     RefCell::new(Instant::now())
 }
\ No newline at end of file
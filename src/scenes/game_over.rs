@@ -8,6 +8,7 @@ widget_ids! {
 		
 		title,
         score,
+        final_taunt,
 		
 		back
 	}
@@ -40,7 +41,7 @@ impl Scene for GameOver {
 			.w_h(ui.win_w, ui.win_h)
 			.set(ids.root, ui);
 
-        widget::Text::new("YOU DIED!")
+        widget::Text::new(match data_store.has("fled") { true => "YOU FLED!", false => "YOU DIED!" })
             .font_id(*fonts.get("lato").unwrap())
             .font_size(64)
             .color(theme.text_primary)
@@ -56,6 +57,16 @@ impl Scene for GameOver {
             .x_align_to(ids.root, Align::Middle)
             .set(ids.score, ui);
 
+        if let Some(taunt) = data_store.get_t::<String>("final_taunt") {
+            widget::Text::new(&taunt)
+                .font_id(*fonts.get("lato").unwrap())
+                .font_size(24)
+                .color(theme.text_primary)
+                .y_relative_to(ids.score, -48.0)
+                .x_align_to(ids.root, Align::Middle)
+                .set(ids.final_taunt, ui);
+        }
+
         if widget::Button::new()
             .color(theme.button_normal)
             .hover_color(theme.button_hover)
@@ -71,6 +82,8 @@ impl Scene for GameOver {
         {
             data_store.remove("leaderboard_entry");
             data_store.remove("added_leaderboard_entry");
+            data_store.remove("final_taunt");
+            data_store.remove("fled");
             self.next_scene_index = Some(SceneManager::MAIN_MENU);
         }
     }
@@ -0,0 +1,204 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager, game::actions::GameData},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		title,
+		player_health,
+
+		option_buttons[],
+		description,
+
+		result,
+		scouted_enemy,
+
+		continue_button,
+	}
+}
+
+generate_scene!(Interlude -> Ids);
+
+impl Scene for Interlude {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let game_data = *data_store.get_mut_t::<GameData>("game_data").unwrap();
+		let options = game_data.difficulty_settings.interlude_options.clone();
+		if self.ids.option_buttons.len() != options.len() {
+			self.ids.option_buttons.resize(options.len(), &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		if !data_store.has("interlude_state") {
+			data_store.set("interlude_state", InterludeState::default());
+		}
+		let state = *data_store.get_mut_t::<InterludeState>("interlude_state").unwrap();
+
+		const BUTTON_HEIGHT: f64 = 64.0;
+		const BUTTON_WIDTH: f64 = 320.0;
+		const BUTTON_SPACING: f64 = 8.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		let title = match game_data.map.borrow().as_ref().map(|map| map.current().kind) {
+			Some(data::NodeKind::Rest) => String::from("You find a quiet place to rest."),
+			_ => format!("Enemy defeated! {} down so far.", game_data.enemies_killed.borrow()),
+		};
+		widget::Text::new(&title)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(48)
+			.mid_top_with_margin_on(ids.root, 64.0)
+			.set(ids.title, ui);
+
+		let player_health_text = {
+			let player = game_data.player.borrow();
+			format!("{}: {} / {} health", player.name, player.health, player.get_max_health())
+		};
+		widget::Text::new(&player_health_text)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(28)
+			.mid_bottom_with_margin_on(ids.title, -(BUTTON_SPACING * 2.0 + 28.0))
+			.set(ids.player_health, ui);
+
+		let base_button = widget::Button::new()
+			.w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.border(0.0)
+			.label_font_size(28)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		let mut hovered_description: Option<&str> = None;
+		for (i, option) in options.iter().enumerate() {
+			let button_id = ids.option_buttons[i];
+			let button = match i {
+				0 => base_button.clone().mid_bottom_with_margin_on(ids.player_health, -(BUTTON_SPACING * 6.0 + BUTTON_HEIGHT)),
+				_ => base_button.clone().mid_bottom_with_margin_on(ids.option_buttons[i - 1], -(BUTTON_SPACING + BUTTON_HEIGHT)),
+			};
+			let button = match (state.chosen, state.chosen == Some(i)) {
+				(_, true) => button.color(theme.accend_color_secondary).label_color(theme.text_light),
+				(Some(_), false) => button.color(theme.button_disabled).hover_color(theme.button_disabled).press_color(theme.button_disabled),
+				(None, false) => button.color(theme.button_normal),
+			};
+
+			if button
+				.label(&option.name)
+				.set(button_id, ui)
+				.was_clicked()
+				&& state.chosen.is_none()
+			{
+				state.chosen = Some(i);
+				state.result = game_data.apply_interlude_option(option);
+				game_data.autosave_if_needed();
+				state.scouted = option.effect == data::InterludeEffect::Scout;
+				scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+			}
+
+			if ui.widget_input(button_id).mouse().is_some() {
+				hovered_description = Some(&option.description);
+			}
+		}
+
+		let description = widget::Text::new(hovered_description.unwrap_or(""))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20);
+		match ids.option_buttons.last() {
+			Some(&last_button) => description.mid_bottom_with_margin_on(last_button, -(BUTTON_SPACING * 2.0 + 20.0)),
+			None => description.mid_bottom_with_margin_on(ids.player_health, -(BUTTON_SPACING * 2.0 + 20.0)),
+		}
+		.set(ids.description, ui);
+
+		widget::Text::new(&state.result)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(24)
+			.mid_bottom_with_margin_on(ids.description, -(BUTTON_SPACING * 4.0 + 24.0))
+			.set(ids.result, ui);
+
+		let scouted_text = match state.scouted {
+			true => scouted_enemy_text(game_data),
+			false => String::new(),
+		};
+		widget::Text::new(&scouted_text)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(22)
+			.center_justify()
+			.line_spacing(6.0)
+			.mid_bottom_with_margin_on(ids.result, -(BUTTON_SPACING * 2.0 + 22.0))
+			.set(ids.scouted_enemy, ui);
+
+		let mut continue_button = base_button.clone()
+			.label("Continue")
+			.color(theme.button_normal)
+			.mid_bottom_with_margin_on(ids.root, 24.0);
+		if state.chosen.is_none() {
+			continue_button = continue_button
+				.color(theme.button_disabled)
+				.hover_color(theme.button_disabled)
+				.press_color(theme.button_disabled);
+		}
+
+		if continue_button
+			.set(ids.continue_button, ui)
+			.was_clicked()
+			&& state.chosen.is_some()
+		{
+			data_store.remove("interlude_state");
+			data_store.remove("player_health_current");
+			data_store.remove("enemy_health_current");
+			self.next_scene_index = match game_data.map.borrow().is_some() {
+				true => Some(SceneManager::MAP),
+				false => Some(SceneManager::GAME),
+			};
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
+
+fn scouted_enemy_text(game_data: &GameData) -> String {
+	let enemy = game_data.enemy.borrow();
+	let mut lines = vec![
+		format!("{} - {} health", enemy.name, enemy.get_max_health()),
+		format!(
+			"Vitality {}, Attack {}, Defense {}, Stamina {}",
+			enemy.stats.vitality, enemy.stats.attack, enemy.stats.defense, enemy.stats.stamina,
+		),
+	];
+	if enemy.is_elite() {
+		lines.push(format!("Elite: {}", enemy.affix_names().join(", ")));
+	}
+	lines.join("\n")
+}
+
+#[derive(Debug, Clone, Default)]
+struct InterludeState {
+	chosen: Option<usize>,
+	result: String,
+	scouted: bool,
+}
@@ -1,6 +1,6 @@
 use std::time::{Duration, Instant};
 
-use crate::scenes::game::actions::{CharacterState, GameData, Turn};
+use crate::scenes::{SaveBrowserMode, game::actions::GameData};
 use crate::{
 	Scene, SceneManager,
 	generate_scene,
@@ -27,8 +27,11 @@ widget_ids! {
 		button_change_theme,
 		text_change_theme,
 
+		text_active_mods,
+
 		leaderboard_container,
 		leaderboard_title,
+		leaderboard_toggle,
 		leaderboard_text_0,
 		leaderboard_text_1,
 		leaderboard_text_2,
@@ -39,6 +42,12 @@ widget_ids! {
 		leaderboard_text_7,
 		leaderboard_text_8,
 		leaderboard_text_9,
+
+		recover_overlay,
+		recover_panel,
+		recover_text,
+		button_recover,
+		button_discard,
 	}
 }
 
@@ -78,7 +87,13 @@ impl Scene for MainMenu {
 			leaderboard = *data_store.get_t::<data::Leaderboard>("leaderboard").unwrap();
 		}
 
-		let has_save_data = crate::ASSETS_FOLDER.join("data/runtime/current_game.yaml").exists();
+		let active_mods = data::Mod::active();
+		let show_custom = data_store.get_t::<bool>("show_custom_leaderboard").map_or(false, |show_custom| **show_custom);
+		let leaderboard_entries = leaderboard.entries_for(&data::Mod::active_ids(), show_custom);
+
+		// Saves of runs that already ended can't be continued, but are still erased
+		let has_save_data = data::SaveSlot::any_resumable();
+		let has_any_saves = data::SaveSlot::any_used();
 		let has_leaderboard = !leaderboard.is_empty;
 		
 		widget::Canvas::new()
@@ -134,14 +149,8 @@ impl Scene for MainMenu {
 			.set(ids.button_continue, ui)
 			.was_clicked() {
 				if has_save_data {
-					let file = std::fs::File::open(crate::ASSETS_FOLDER.join("data/runtime/current_game.yaml")).unwrap();
-					let game_data: GameData = serde_yaml::from_reader(file).unwrap();
-					game_data.player.borrow_mut().state = CharacterState::Idle;
-					game_data.enemy.borrow_mut().state = CharacterState::Idle;
-
-					data_store.set("game_data", game_data);
-
-					self.next_scene_index = Some(SceneManager::GAME);
+					data_store.set("save_browser_mode", SaveBrowserMode::Load);
+					self.next_scene_index = Some(SceneManager::SAVE_BROWSER);
 				}
 			}
 
@@ -151,7 +160,7 @@ impl Scene for MainMenu {
 			.mid_bottom_of(ids.button_continue)
 			.down(BUTTON_SPACING);
 
-		if !has_save_data && !has_leaderboard {
+		if !has_any_saves && !has_leaderboard {
 			erase_data_button = erase_data_button
 			.color(theme.button_disabled)
 			.hover_color(theme.button_disabled)
@@ -162,14 +171,22 @@ impl Scene for MainMenu {
 		if erase_data_button
 			.set(ids.button_erase_data, ui)
 			.was_clicked() {
-				#[allow(unused_must_use)]
-				if has_save_data || has_leaderboard {
-					if has_save_data {
-						std::fs::remove_file(crate::ASSETS_FOLDER.join("data/runtime/current_game.yaml"));
+				if has_any_saves || has_leaderboard {
+					let mut results = vec![
+						data::user_data().delete(data::Nemesis::FILE),
+						data::user_data().delete(data::Profile::FILE),
+					];
+					if has_any_saves {
+						results.push(data::SaveSlot::delete_all());
+					}
+					if has_leaderboard {
+						results.push(data::Leaderboard::erase());
+					}
+					for error in results.into_iter().filter_map(Result::err) {
+						eprintln!("{}", error);
 					}
 
 					if has_leaderboard {
-						std::fs::remove_file(crate::ASSETS_FOLDER.join("data/runtime/leaderboard.yaml"));
 						data_store.remove("leaderboard");
 						data_store.set("leaderboard", data::Leaderboard::make(Some(10)));
 					}
@@ -215,12 +232,28 @@ impl Scene for MainMenu {
 			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("wakeup error: {}", e));
 		}
 
+		if !active_mods.is_empty() {
+			let mod_names: Vec<String> = active_mods.iter().map(|loaded| format!("{} {}", loaded.name, loaded.version)).collect();
+			widget::Text::new(&format!("Mods: {}", mod_names.join(", ")))
+				.font_size(20)
+				.font_id(*fonts.get("lato").unwrap())
+				.color(theme.text_primary)
+				.right_justify()
+				.bottom_right_with_margins_on(ids.root, 16.0, 16.0)
+				.set(ids.text_active_mods, ui);
+		}
+
 		widget::Rectangle::fill_with([leaderboard_width, leaderboard_height], theme.panel_dark)
 			.y_align_to(ids.root, Align::Middle)
 			.x_place_on(ids.root, Place::Start(Some(LEADERBOARD_SPACING_X)))
 			.set(ids.leaderboard_container, ui);
 
-		widget::Text::new("Leaderboard")
+		widget::Text::new(match (active_mods.is_empty(), show_custom) {
+			(true, false) => "Leaderboard",
+			(true, true) => "Custom Leaderboard",
+			(false, false) => "Modded Leaderboard",
+			(false, true) => "Modded Custom Leaderboard",
+		})
 			.font_size(48)
 			.font_id(*fonts.get("lato").unwrap())
 			.color(theme.text_primary)
@@ -228,6 +261,24 @@ impl Scene for MainMenu {
 			.y_place_on(ids.leaderboard_container, Place::End(Some(8.0)))
 			.set(ids.leaderboard_title, ui);
 
+		// Runs on custom difficulties are kept off the official board
+		if widget::Button::new()
+			.label(match show_custom { true => "Official", false => "Custom" })
+			.label_font_size(20)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap())
+			.w_h(120.0, 40.0)
+			.border(0.0)
+			.color(theme.button_normal)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.top_right_with_margins_on(ids.leaderboard_container, 12.0, 12.0)
+			.set(ids.leaderboard_toggle, ui)
+			.was_clicked()
+		{
+			data_store.set("show_custom_leaderboard", !show_custom);
+		}
+
 		let text_ids = [
 			ids.leaderboard_text_0, ids.leaderboard_text_1, ids.leaderboard_text_2, ids.leaderboard_text_3,
 			ids.leaderboard_text_4, ids.leaderboard_text_5, ids.leaderboard_text_6, ids.leaderboard_text_7,
@@ -237,9 +288,9 @@ impl Scene for MainMenu {
 		let leaderboard_text_height = leaderboard_height - 16.0 * 2.0 /* Top+bottom spacing */ - 48.0 - 16.0 /* Title spacing */;
 		let leaderboard_text_spacing = leaderboard_text_height / leaderboard.capacity as f64;
 
-		let entry0_text: String = match leaderboard.get(0) {
+		let entry0_text: String = match leaderboard_entries.get(0) {
 			None => String::from(" 1. "),
-			Some(entry) => format!("{:>2}. [{}] {} - {}", 1, entry.difficulty.as_str(), entry.name, entry.score),
+			Some(entry) => format!("{}. [{}] {} - {}{}{}", leaderboard_rank(1, entry), leaderboard_tag(entry), entry.name, entry.score, leaderboard_modifiers(entry), leaderboard_unverified(entry)),
 		};
 		widget::Text::new(&entry0_text)
 			.font_size(24)
@@ -251,9 +302,9 @@ impl Scene for MainMenu {
 			.set(text_ids[0], ui);
 
 		for i in 1..leaderboard.capacity {
-			let entry_text: String = match leaderboard.get(i) {
+			let entry_text: String = match leaderboard_entries.get(i) {
 				None => format!("{:>2}. ", i + 1),
-				Some(entry) => format!("{:>2}. [{}] {} - {}", i + 1, entry.difficulty.as_str(), entry.name, entry.score),
+				Some(entry) => format!("{}. [{}] {} - {}{}{}", leaderboard_rank(i + 1, entry), leaderboard_tag(entry), entry.name, entry.score, leaderboard_modifiers(entry), leaderboard_unverified(entry)),
 			};
 			widget::Text::new(&entry_text)
 				.font_size(24)
@@ -264,8 +315,103 @@ impl Scene for MainMenu {
 				.h(0.0)
 				.set(text_ids[i], ui);
 		}
+
+		// Drawn last so it sits on top of, and blocks, the rest of the menu
+		let recoverable = data_store.get_t::<data::SaveMetadata>("recoverable_autosave").map(|metadata| (**metadata).clone());
+		if let Some(autosave) = recoverable {
+			widget::Canvas::new()
+				.color(theme.background.alpha(0.8))
+				.border(0.0)
+				.w_h(ui.win_w, ui.win_h)
+				.middle_of(ids.root)
+				.set(ids.recover_overlay, ui);
+
+			widget::Canvas::new()
+				.color(theme.panel_dark)
+				.border(0.0)
+				.w_h(640.0, 280.0)
+				.middle_of(ids.recover_overlay)
+				.set(ids.recover_panel, ui);
+
+			let text = format!(
+				"The game didn't close properly last time. An unfinished run of {} ({} kills) from {} was found. Recover it?",
+				autosave.character_name,
+				autosave.kills,
+				autosave.saved_ago_text(),
+			);
+			widget::Text::new(&text)
+				.color(theme.text_primary)
+				.font_id(*fonts.get("lato").unwrap())
+				.font_size(24)
+				.w(640.0 - 64.0)
+				.wrap_by_word()
+				.center_justify()
+				.mid_top_with_margin_on(ids.recover_panel, 32.0)
+				.set(ids.recover_text, ui);
+
+			if base_button.clone()
+				.w(240.0)
+				.label("Recover")
+				.bottom_left_with_margins_on(ids.recover_panel, 32.0, 64.0)
+				.set(ids.button_recover, ui)
+				.was_clicked()
+			{
+				match GameData::load_autosave() {
+					Ok(game_data) => {
+						data_store.set("game_data", game_data);
+						self.next_scene_index = Some(SceneManager::GAME);
+					}
+					Err(error) => eprintln!("{}", error),
+				}
+				data_store.remove("recoverable_autosave");
+				scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+			}
+
+			if base_button.clone()
+				.w(240.0)
+				.label("Discard")
+				.bottom_right_with_margins_on(ids.recover_panel, 32.0, 64.0)
+				.set(ids.button_discard, ui)
+				.was_clicked()
+			{
+				if let Err(error) = data::Autosave::clear() {
+					eprintln!("{}", error);
+				}
+				data_store.remove("recoverable_autosave");
+				scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+			}
+		}
 	}
 
 	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
 	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
 }
+
+/* Unverified entries are listed below the rest but not given a rank */
+fn leaderboard_rank(rank: usize, entry: &data::LeaderboardEntry) -> String {
+	match entry.is_verified() {
+		true => format!("{:>2}", rank),
+		false => String::from(" ?"),
+	}
+}
+
+fn leaderboard_unverified(entry: &data::LeaderboardEntry) -> &'static str {
+	match entry.is_verified() {
+		true => "",
+		false => " (unverified)",
+	}
+}
+
+fn leaderboard_tag(entry: &data::LeaderboardEntry) -> String {
+	match entry.custom {
+		true => format!("custom: {}", entry.difficulty.as_str()),
+		false => entry.difficulty.as_str().to_string(),
+	}
+}
+
+fn leaderboard_modifiers(entry: &data::LeaderboardEntry) -> String {
+	match entry.modifiers.is_empty() {
+		true => String::new(),
+		false => format!(" (x{:.2}: {})", entry.score_multiplier, entry.modifiers.join(", ")),
+	}
+}
@@ -0,0 +1,148 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager, game::actions::GameData},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		title,
+		subtitle,
+
+		edge_lines[],
+		node_buttons[],
+	}
+}
+
+generate_scene!(Map -> Ids);
+
+impl Scene for Map {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let game_data = *data_store.get_mut_t::<GameData>("game_data").unwrap();
+		game_data.ensure_map(rand::random());
+		let map = game_data.map.borrow().clone().unwrap();
+
+		let node_count: usize = map.layers.iter().map(|layer| layer.len()).sum();
+		let edge_count: usize = map.layers.iter().flatten().map(|node| node.next.len()).sum();
+		if self.ids.node_buttons.len() != node_count {
+			self.ids.node_buttons.resize(node_count, &mut ui.widget_id_generator());
+		}
+		if self.ids.edge_lines.len() != edge_count {
+			self.ids.edge_lines.resize(edge_count, &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		const NODE_WIDTH: f64 = 112.0;
+		const NODE_HEIGHT: f64 = 48.0;
+		const NODE_SPACING: f64 = 96.0;
+		const MARGIN: f64 = 128.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Text::new(&format!("Act {}", map.act))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(48)
+			.mid_top_with_margin_on(ids.root, 48.0)
+			.set(ids.title, ui);
+
+		widget::Text::new("Choose where to go next")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(24)
+			.mid_bottom_with_margin_on(ids.title, -(16.0 + 24.0))
+			.set(ids.subtitle, ui);
+
+		// Layers run left to right, the nodes of a layer are stacked around the middle of the window
+		let win_w = ui.win_w;
+		let column_spacing = (win_w - MARGIN * 2.0) / (map.layers.len().max(2) - 1) as f64;
+		let node_position = |layer: usize, node: usize| -> [f64; 2] {
+			let count = map.layers[layer].len() as f64;
+			[
+				-win_w / 2.0 + MARGIN + layer as f64 * column_spacing,
+				((count - 1.0) / 2.0 - node as f64) * NODE_SPACING - 32.0,
+			]
+		};
+
+		let choices = map.choices();
+		let mut edge_index = 0;
+		for (layer, nodes) in map.layers.iter().enumerate() {
+			for (i, node) in nodes.iter().enumerate() {
+				for &next in &node.next {
+					let travelled = map.path.contains(&(layer, i)) && map.path.contains(&(layer + 1, next));
+					widget::Line::abs(node_position(layer, i), node_position(layer + 1, next))
+						.color(match travelled {
+							true => theme.accend_color_secondary,
+							false => theme.button_disabled,
+						})
+						.thickness(match travelled { true => 4.0, false => 2.0 })
+						.set(ids.edge_lines[edge_index], ui);
+					edge_index += 1;
+				}
+			}
+		}
+
+		let mut node_index = 0;
+		let mut chosen = None;
+		for (layer, nodes) in map.layers.iter().enumerate() {
+			for (i, node) in nodes.iter().enumerate() {
+				let [x, y] = node_position(layer, i);
+				let button = widget::Button::new()
+					.w_h(NODE_WIDTH, NODE_HEIGHT)
+					.x_y(x, y)
+					.border(0.0)
+					.label(node.kind.name())
+					.label_font_size(20)
+					.label_font_id(*fonts.get("lato").unwrap());
+
+				let button = match (map.position == (layer, i), choices.contains(&(layer, i)), map.path.contains(&(layer, i))) {
+					(true, _, _) => button.color(theme.accent_color).hover_color(theme.accent_color).press_color(theme.accent_color).label_color(theme.text_light),
+					(_, true, _) => button.color(theme.button_normal).hover_color(theme.button_hover).press_color(theme.button_press).label_color(theme.text_secondary),
+					(_, _, true) => button.color(theme.accend_color_secondary).hover_color(theme.accend_color_secondary).press_color(theme.accend_color_secondary).label_color(theme.text_light),
+					_ => button.color(theme.panel_dark).hover_color(theme.panel_dark).press_color(theme.panel_dark).label_color(theme.text_primary),
+				};
+
+				if button.set(ids.node_buttons[node_index], ui).was_clicked() && choices.contains(&(layer, i)) {
+					chosen = Some((layer, i));
+				}
+				node_index += 1;
+			}
+		}
+
+		if let Some(position) = chosen {
+			let entered = game_data.enter_map_node(position);
+			game_data.autosave_if_needed();
+			match entered {
+				Ok(kind) if kind.is_battle() => self.next_scene_index = Some(SceneManager::GAME),
+				Ok(data::NodeKind::Event) => self.next_scene_index = Some(SceneManager::EVENT),
+				Ok(data::NodeKind::Shop) => self.next_scene_index = Some(SceneManager::SHOP),
+				Ok(_) if !game_data.difficulty_settings.interlude_options.is_empty() => self.next_scene_index = Some(SceneManager::INTERLUDE),
+				// Without any interlude options there is nothing to do here, so the player just walks on
+				Ok(_) => {}
+				Err(error) => eprintln!("{}", error),
+			}
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
@@ -0,0 +1,181 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		title,
+
+		modifier_buttons[],
+		description,
+		score_multiplier,
+
+		ironman,
+		back,
+		next,
+	}
+}
+
+generate_scene!(ModifierSelection -> Ids);
+
+impl Scene for ModifierSelection {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let modifiers = data::RunModifier::run_modifiers();
+		if self.ids.modifier_buttons.len() != modifiers.len() {
+			self.ids.modifier_buttons.resize(modifiers.len(), &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		if !data_store.has("run_modifiers") {
+			data_store.set("run_modifiers", Vec::<data::RunModifier>::new());
+		}
+		let selected = *data_store.get_mut_t::<Vec<data::RunModifier>>("run_modifiers").unwrap();
+
+		const BUTTON_HEIGHT: f64 = 48.0;
+		const BUTTON_WIDTH: f64 = 320.0;
+		const BUTTON_SPACING: f64 = 8.0;
+		const TITLE_HEIGHT: f64 = 64.0;
+		let button_count = modifiers.len() as f64;
+		let total_buttons_height = BUTTON_HEIGHT * button_count + BUTTON_SPACING * (button_count - 1.0).max(0.0);
+
+		let remaining_height = ui.win_h - total_buttons_height - 2.0 * TITLE_HEIGHT;
+		let vertical_spacing = remaining_height / 2.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Text::new("Choose run modifiers")
+			.color(theme.text_primary)
+			.align_middle_y_of(ids.root)
+			.align_middle_x()
+			.up(-vertical_spacing)
+			.h(0.0)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size((TITLE_HEIGHT / 2.0) as u32)
+			.set(ids.title, ui);
+
+		let base_button = widget::Button::new()
+			.w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.border(0.0)
+			.label_font_size(24)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		let mut hovered_description: Option<&str> = None;
+		for (i, modifier) in modifiers.iter().enumerate() {
+			let button_id = ids.modifier_buttons[i];
+			let is_selected = selected.iter().any(|selected| selected.id == modifier.id);
+			let button = match i {
+				0 => base_button.clone().mid_top_with_margin_on(ids.title, BUTTON_HEIGHT),
+				_ => base_button.clone().mid_bottom_with_margin_on(ids.modifier_buttons[i - 1], -(BUTTON_SPACING + BUTTON_HEIGHT)),
+			};
+			let button = match is_selected {
+				true => button.color(theme.accend_color_secondary).label_color(theme.text_light),
+				false => button.color(theme.button_normal),
+			};
+
+			if button
+				.label(&format!("{} (x{:.2})", modifier.name, modifier.score_multiplier))
+				.set(button_id, ui)
+				.was_clicked() {
+					if is_selected {
+						selected.retain(|selected| selected.id != modifier.id);
+					} else {
+						selected.push(modifier.clone());
+					}
+					scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+				}
+
+			if ui.widget_input(button_id).mouse().is_some() {
+				hovered_description = Some(&modifier.description);
+			}
+		}
+
+		let score_multiplier = data::RunModifier::combined_score_multiplier(selected);
+		let summary_text = format!("Score multiplier: x{:.2}", score_multiplier);
+		let summary = widget::Text::new(&summary_text)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(24);
+		match ids.modifier_buttons.last() {
+			Some(&last_button) => summary.mid_bottom_with_margin_on(last_button, -(BUTTON_SPACING * 2.0 + 24.0)),
+			None => summary.mid_top_with_margin_on(ids.title, BUTTON_HEIGHT),
+		}
+		.set(ids.score_multiplier, ui);
+
+		widget::Text::new(hovered_description.unwrap_or(""))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.mid_bottom_with_margin_on(ids.score_multiplier, -(BUTTON_SPACING * 2.0 + 20.0))
+			.set(ids.description, ui);
+
+		let bottom_button = widget::Button::new()
+			.color(theme.button_normal)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.label_color(theme.text_secondary)
+			.border(0.0)
+			.label_font_id(*fonts.get("lato").unwrap())
+			.label_font_size(24)
+			.w_h(320.0, 48.0);
+
+		// Only the autosave is kept and leaving the run ends it, see GameData::ironman
+		let ironman = data_store.get_t::<bool>("ironman").map_or(false, |ironman| **ironman);
+		let ironman_button = bottom_button.clone()
+			.label(match ironman { true => "Ironman: On", false => "Ironman: Off" })
+			.mid_bottom_with_margin_on(ids.root, 24.0);
+		let ironman_button = match ironman {
+			true => ironman_button.color(theme.accend_color_secondary).label_color(theme.text_light),
+			false => ironman_button,
+		};
+		if ironman_button.set(ids.ironman, ui).was_clicked() {
+			data_store.set("ironman", !ironman);
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+
+		if bottom_button.clone()
+			.label("Back")
+			.bottom_left_with_margins_on(ids.root, 24.0, 48.0)
+			.set(ids.back, ui)
+			.was_clicked()
+		{
+			data_store.remove("run_modifiers");
+			data_store.remove("ironman");
+			self.next_scene_index = Some(SceneManager::DIFFICULTY_SELECTION);
+		}
+
+		if bottom_button.clone()
+			.label("Continue")
+			.bottom_right_with_margins_on(ids.root, 24.0, 48.0)
+			.set(ids.next, ui)
+			.was_clicked()
+		{
+			self.next_scene_index = Some(SceneManager::CHARACTER_CREATION);
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
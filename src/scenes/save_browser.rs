@@ -0,0 +1,254 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager, game::actions::GameData},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		title,
+		text_name,
+		textbox_name,
+
+		slot_buttons[],
+		delete_buttons[],
+
+		text_error,
+		button_back,
+	}
+}
+
+generate_scene!(SaveBrowser -> Ids);
+
+/* Whether the browser was opened to continue a run or to save the one in progress */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveBrowserMode {
+	Load,
+	Save,
+}
+
+impl Scene for SaveBrowser {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let mode = data_store.get_t::<SaveBrowserMode>("save_browser_mode").map_or(SaveBrowserMode::Load, |mode| **mode);
+		if !data_store.has("save_browser_state") {
+			data_store.set("save_browser_state", SaveBrowserState { slots: data::SaveSlot::all(), name: String::new(), error: None });
+		}
+		let state = *data_store.get_mut_t::<SaveBrowserState>("save_browser_state").unwrap();
+
+		if self.ids.slot_buttons.len() != data::SAVE_SLOT_COUNT {
+			self.ids.slot_buttons.resize(data::SAVE_SLOT_COUNT, &mut ui.widget_id_generator());
+			self.ids.delete_buttons.resize(data::SAVE_SLOT_COUNT, &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		const SLOT_WIDTH: f64 = 840.0;
+		const DELETE_WIDTH: f64 = 120.0;
+		const BUTTON_HEIGHT: f64 = 64.0;
+		const BUTTON_SPACING: f64 = 8.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Text::new(match mode { SaveBrowserMode::Load => "Load Game", SaveBrowserMode::Save => "Save Game" })
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(56)
+			.mid_top_with_margin_on(ids.root, 64.0)
+			.set(ids.title, ui);
+
+		if mode == SaveBrowserMode::Save {
+			widget::Text::new("Save name (leave empty to keep the slot's name)")
+				.color(theme.text_primary)
+				.font_id(*fonts.get("lato").unwrap())
+				.font_size(24)
+				.down_from(ids.title, 32.0)
+				.x_align_to(ids.root, conrod_core::position::Align::Middle)
+				.set(ids.text_name, ui);
+
+			for event in widget::TextBox::new(&state.name)
+				.font_size(24)
+				.w_h(SLOT_WIDTH + DELETE_WIDTH + BUTTON_SPACING, 40.0)
+				.color(theme.panel_light)
+				.text_color(theme.text_dark)
+				.border(0.0)
+				.down_from(ids.text_name, 8.0)
+				.x_align_to(ids.root, conrod_core::position::Align::Middle)
+				.set(ids.textbox_name, ui)
+			{
+				if let widget::text_box::Event::Update(name) = event {
+					state.name = name;
+				}
+			}
+		}
+
+		let base_button = widget::Button::new()
+			.h(BUTTON_HEIGHT)
+			.border(0.0)
+			.color(theme.button_normal)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.label_font_size(24)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		let (first_slot_anchor, first_slot_margin) = match mode {
+			SaveBrowserMode::Load => (ids.title, 48.0),
+			SaveBrowserMode::Save => (ids.textbox_name, 32.0),
+		};
+		let mut chosen = None;
+		let mut deleted = None;
+		for slot in &state.slots {
+			let i = slot.index;
+			let label = slot_label(slot);
+			let button = base_button.clone()
+				.w(SLOT_WIDTH)
+				.label(&label);
+			let button = match i {
+				// Centre the slot and its delete button together
+				0 => button.down_from(first_slot_anchor, first_slot_margin).x_relative_to(ids.root, -(DELETE_WIDTH + BUTTON_SPACING) / 2.0),
+				_ => button.down_from(ids.slot_buttons[i - 1], BUTTON_SPACING),
+			};
+			let can_pick = mode == SaveBrowserMode::Save || slot.is_resumable();
+			let button = match can_pick {
+				true => button,
+				false => button.color(theme.button_disabled).hover_color(theme.button_disabled).press_color(theme.button_disabled),
+			};
+			if button.set(ids.slot_buttons[i], ui).was_clicked() && can_pick {
+				chosen = Some(i);
+			}
+
+			let delete_button = base_button.clone()
+				.w(DELETE_WIDTH)
+				.label("Delete")
+				.right_from(ids.slot_buttons[i], BUTTON_SPACING);
+			let delete_button = match slot.is_used() {
+				true => delete_button,
+				false => delete_button.color(theme.button_disabled).hover_color(theme.button_disabled).press_color(theme.button_disabled),
+			};
+			if delete_button.set(ids.delete_buttons[i], ui).was_clicked() && slot.is_used() {
+				deleted = Some(i);
+			}
+		}
+
+		widget::Text::new(state.error.as_deref().unwrap_or(""))
+			.color(theme.accent_color)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(24)
+			.w(SLOT_WIDTH + DELETE_WIDTH + BUTTON_SPACING)
+			.wrap_by_word()
+			.center_justify()
+			.down_from(ids.slot_buttons[data::SAVE_SLOT_COUNT - 1], 24.0)
+			.x_align_to(ids.root, conrod_core::position::Align::Middle)
+			.set(ids.text_error, ui);
+
+		if let Some(index) = deleted {
+			state.error = data::SaveSlot::delete(index).err();
+			state.slots = data::SaveSlot::all();
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+
+		if let Some(index) = chosen {
+			match mode {
+				SaveBrowserMode::Load if state.slots[index].error.is_some() => state.error = state.slots[index].error.clone(),
+				SaveBrowserMode::Load => match GameData::load_from_slot(index) {
+					Ok(game_data) => {
+						data_store.set("game_data", game_data);
+						self.next_scene_index = leave(data_store, SceneManager::GAME);
+					}
+					Err(error) => state.error = Some(error),
+				},
+				SaveBrowserMode::Save => {
+					let name = match state.name.trim() {
+						"" => state.slots[index].metadata.as_ref().map_or(data::SaveMetadata::default_name(index), |metadata| metadata.name.clone()),
+						name => name.to_string(),
+					};
+					let game_data = *data_store.get_mut_t::<GameData>("game_data").unwrap();
+					match game_data.save_to_slot(index, name) {
+						Ok(()) => {
+							if let Err(error) = data::Autosave::clear() {
+								eprintln!("{}", error);
+							}
+							data_store.remove("game_data");
+							data_store.remove("player_health_current");
+							data_store.remove("enemy_health_current");
+							self.next_scene_index = leave(data_store, SceneManager::MAIN_MENU);
+						}
+						Err(error) => state.error = Some(error),
+					}
+				}
+			}
+			scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+		}
+
+		if base_button.clone()
+			.w(320.0)
+			.label("Back")
+			.mid_bottom_with_margin_on(ids.root, 24.0)
+			.set(ids.button_back, ui)
+			.was_clicked()
+		{
+			self.next_scene_index = leave(data_store, match mode {
+				SaveBrowserMode::Load => SceneManager::MAIN_MENU,
+				SaveBrowserMode::Save => SceneManager::GAME,
+			});
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
+
+/* Forgets the browser's state so it starts fresh next time, and switches to `scene` */
+fn leave(data_store: &data::DataStore, scene: usize) -> Option<usize> {
+	data_store.remove("save_browser_state");
+	data_store.remove("save_browser_mode");
+	Some(scene)
+}
+
+fn slot_label(slot: &data::SaveSlot) -> String {
+	match &slot.metadata {
+		None if slot.error.is_some() => format!("{} - can't be read", data::SaveMetadata::default_name(slot.index)),
+		None => format!("{} - empty", data::SaveMetadata::default_name(slot.index)),
+		Some(metadata) => {
+			let character_type = data::CharacterType::all()
+				.iter()
+				.find(|character_type| character_type.id == metadata.character_type)
+				.map_or(metadata.character_type.clone(), |character_type| character_type.display_name.clone());
+			format!(
+				"{} - {} ({}), {}, {} kills, {} - {}{}",
+				metadata.name,
+				metadata.character_name,
+				character_type,
+				metadata.difficulty.display_name(),
+				metadata.kills,
+				metadata.playtime_text(),
+				metadata.saved_ago_text(),
+				if metadata.finished { " (finished)" } else { "" },
+			)
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+struct SaveBrowserState {
+	slots: Vec<data::SaveSlot>,
+	name: String,
+	error: Option<String>,
+}
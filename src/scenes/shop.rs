@@ -0,0 +1,182 @@
+use conrod_core::{
+	widget, Colorable, Labelable, Positionable, Sizeable, Borderable, Ui, UiCell, Widget,
+};
+
+use crate::{
+	theme, data, generate_scene,
+	scenes::{Scene, SceneManager, game::actions::GameData},
+};
+
+widget_ids! {
+	pub struct Ids {
+		root,
+
+		panel,
+		title,
+		score,
+
+		item_buttons[],
+		description,
+
+		result,
+
+		leave_button,
+	}
+}
+
+generate_scene!(Shop -> Ids);
+
+impl Scene for Shop {
+	fn build(
+		&mut self,
+		ui: &mut UiCell,
+		images: &std::collections::HashMap<String, conrod_core::image::Id>,
+		image_map: &conrod_core::image::Map::<glium::texture::SrgbTexture2d>,
+		fonts: &std::collections::HashMap<&str, conrod_core::text::font::Id>,
+		scene_manager: &SceneManager,
+		theme: &theme::Theme,
+		data_store: &data::DataStore,
+	) {
+		let game_data = *data_store.get_mut_t::<GameData>("game_data").unwrap();
+
+		if !data_store.has("shop_state") {
+			let stock = data::ShopItem::stock(&mut rand::thread_rng());
+			data_store.set("shop_state", ShopState { bought: vec![false; stock.len()], stock, result: String::new() });
+		}
+		let state = *data_store.get_mut_t::<ShopState>("shop_state").unwrap();
+
+		if self.ids.item_buttons.len() != state.stock.len() {
+			self.ids.item_buttons.resize(state.stock.len(), &mut ui.widget_id_generator());
+		}
+		let ids = &self.ids;
+
+		const PANEL_WIDTH: f64 = 720.0;
+		const PANEL_HEIGHT: f64 = 560.0;
+		const PANEL_MARGIN: f64 = 32.0;
+		const BUTTON_HEIGHT: f64 = 56.0;
+		const BUTTON_SPACING: f64 = 8.0;
+
+		widget::Canvas::new()
+			.color(theme.background)
+			.border(0.0)
+			.w_h(ui.win_w, ui.win_h)
+			.set(ids.root, ui);
+
+		widget::Canvas::new()
+			.color(theme.panel_dark)
+			.border(0.0)
+			.w_h(PANEL_WIDTH, PANEL_HEIGHT)
+			.middle_of(ids.root)
+			.set(ids.panel, ui);
+
+		widget::Text::new("A travelling merchant waves you over.")
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(36)
+			.mid_top_with_margin_on(ids.panel, PANEL_MARGIN)
+			.set(ids.title, ui);
+
+		// Items are paid for with score, so the player can see what a purchase costs them
+		let score = game_data.score();
+		widget::Text::new(&format!("Score to spend: {}", score))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(22)
+			.down_from(ids.title, BUTTON_SPACING * 2.0)
+			.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+			.set(ids.score, ui);
+
+		let base_button = widget::Button::new()
+			.w_h(PANEL_WIDTH - PANEL_MARGIN * 2.0, BUTTON_HEIGHT)
+			.hover_color(theme.button_hover)
+			.press_color(theme.button_press)
+			.border(0.0)
+			.label_font_size(22)
+			.label_color(theme.text_secondary)
+			.label_font_id(*fonts.get("lato").unwrap());
+
+		let mut hovered_description: Option<&str> = None;
+		for (i, item) in state.stock.iter().enumerate() {
+			let button_id = ids.item_buttons[i];
+			let button = match i {
+				0 => base_button.clone().down_from(ids.score, PANEL_MARGIN),
+				_ => base_button.clone().down_from(ids.item_buttons[i - 1], BUTTON_SPACING),
+			};
+			let button = match (state.bought[i], score >= item.price) {
+				(true, _) => button.color(theme.accend_color_secondary).hover_color(theme.accend_color_secondary).press_color(theme.accend_color_secondary).label_color(theme.text_light),
+				(false, false) => button.color(theme.button_disabled).hover_color(theme.button_disabled).press_color(theme.button_disabled),
+				(false, true) => button.color(theme.button_normal),
+			};
+			let label = match state.bought[i] {
+				true => format!("{} - sold", item.name),
+				false => format!("{} - {} score", item.name, item.price),
+			};
+
+			if button
+				.label(&label)
+				.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+				.set(button_id, ui)
+				.was_clicked()
+				&& !state.bought[i]
+			{
+				match game_data.buy(item) {
+					Ok(result) => {
+						game_data.autosave_if_needed();
+						state.bought[i] = true;
+						state.result = result;
+					}
+					Err(error) => state.result = error,
+				}
+				scene_manager.wake_up_events_loop().unwrap_or_else(|e| eprintln!("Failed to wake up events loop: {}", e));
+			}
+
+			if ui.widget_input(button_id).mouse().is_some() {
+				hovered_description = Some(&item.description);
+			}
+		}
+
+		let description = widget::Text::new(hovered_description.unwrap_or(""))
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20);
+		match ids.item_buttons.last() {
+			Some(&last_button) => description.down_from(last_button, PANEL_MARGIN),
+			// Every item was overridden away by mods, the merchant has nothing to sell
+			None => description.down_from(ids.score, PANEL_MARGIN),
+		}
+		.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+		.set(ids.description, ui);
+
+		widget::Text::new(&state.result)
+			.color(theme.text_primary)
+			.font_id(*fonts.get("lato").unwrap())
+			.font_size(20)
+			.center_justify()
+			.line_spacing(6.0)
+			.down_from(ids.description, BUTTON_SPACING * 2.0)
+			.x_align_to(ids.panel, conrod_core::position::Align::Middle)
+			.set(ids.result, ui);
+
+		if base_button.clone()
+			.w(320.0)
+			.label("Leave")
+			.color(theme.button_normal)
+			.mid_bottom_with_margin_on(ids.root, 24.0)
+			.set(ids.leave_button, ui)
+			.was_clicked()
+		{
+			data_store.remove("shop_state");
+			self.next_scene_index = Some(SceneManager::MAP);
+		}
+	}
+
+	fn reset_switch_request(&mut self) { self.next_scene_index = None; }
+	fn get_scene_switch_index(&self) -> std::option::Option<usize> { self.next_scene_index }
+}
+
+#[derive(Debug, Clone)]
+struct ShopState {
+	stock: Vec<&'static data::ShopItem>,
+	bought: Vec<bool>,
+	result: String,
+}
@@ -1,4 +1,5 @@
 use conrod_core::color::Color;
+use serde::{Deserialize, Serialize};
 
 pub const DARK_THEME: Theme = Theme::const_default();
 
@@ -191,6 +192,96 @@ impl Default for Theme {
 }
 
 
+/*
+    Colors a mod can change in `themes/dark.yaml` or `themes/light.yaml`, as
+    "#rrggbb" or "#rrggbbaa". Anything left out keeps the built-in color.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ThemeOverride {
+    text_primary: Option<String>,
+    text_secondary: Option<String>,
+    background: Option<String>,
+    panel_dark: Option<String>,
+    panel_light: Option<String>,
+    accent_color: Option<String>,
+    accent_color_secondary: Option<String>,
+    button_normal: Option<String>,
+    button_hover: Option<String>,
+    button_press: Option<String>,
+    button_disabled: Option<String>,
+}
+
+fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    let color = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(rgbi(color)),
+        8 => Some(rgbai(color)),
+        _ => None,
+    }
+}
+
+impl ThemeOverride {
+    fn options(&self) -> Vec<ThemeOption> {
+        let colors: [(&Option<String>, fn(Color) -> ThemeOption); 11] = [
+            (&self.text_primary, ThemeOption::PrimaryText),
+            (&self.text_secondary, ThemeOption::SecondaryText),
+            (&self.background, ThemeOption::Background),
+            (&self.panel_dark, ThemeOption::PanelDark),
+            (&self.panel_light, ThemeOption::PanelLight),
+            (&self.accent_color, ThemeOption::AccentColor),
+            (&self.accent_color_secondary, ThemeOption::AccendColorSecondary),
+            (&self.button_normal, ThemeOption::ButtonNormal),
+            (&self.button_hover, ThemeOption::ButtonHover),
+            (&self.button_press, ThemeOption::ButtonPressed),
+            (&self.button_disabled, ThemeOption::ButtonDisabled),
+        ];
+
+        colors
+            .iter()
+            .filter_map(|(value, option)| {
+                let value = value.as_ref()?;
+                match parse_hex_color(value) {
+                    Some(color) => Some(option(color)),
+                    None => {
+                        eprintln!("Ignoring theme color {}, expected #rrggbb or #rrggbbaa", value);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/* Applies every mod's `themes/<name>.yaml` on top of `base`, in load order */
+fn modded_theme(name: &str, base: Theme) -> Theme {
+    crate::data::resolve_all_assets(format!("themes/{}.yaml", name))
+        .into_iter()
+        .fold(base, |theme, path| {
+            let theme_override: ThemeOverride = match std::fs::File::open(&path).map(serde_yaml::from_reader) {
+                Ok(Ok(theme_override)) => theme_override,
+                Ok(Err(error)) => {
+                    eprintln!("Failed to load {}: {}", path.display(), error);
+                    return theme;
+                }
+                Err(error) => {
+                    eprintln!("Failed to open {}: {}", path.display(), error);
+                    return theme;
+                }
+            };
+            theme.with_options(&theme_override.options())
+        })
+}
+
+pub fn dark_theme() -> Theme {
+    modded_theme("dark", DARK_THEME)
+}
+
+pub fn light_theme() -> Theme {
+    modded_theme("light", LIGHT_THEME)
+}
+
 pub struct ThemeManager {
     pub active_theme: Box<Theme>
 }
@@ -198,7 +289,7 @@ pub struct ThemeManager {
 impl ThemeManager {
     pub fn new() -> Self {
         ThemeManager {
-            active_theme: Box::new(DARK_THEME)
+            active_theme: Box::new(dark_theme())
         }
     }
 